@@ -1,9 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use hardware::Hardware;
+use thiserror::Error;
 
 use crate::config::Config;
-use crate::config::{control::Control, fan::Fan, temp::Temp};
+use crate::config::{
+    control::Control, emergency::Emergency, expression::Expression, fan::Fan, metric::Metric,
+    temp::Temp,
+};
 
 use crate::id::{Id, IdGenerator};
 use crate::node::{self, Node, NodeType, NodeTypeLight, ToNode};
@@ -12,11 +16,35 @@ use crate::utils::RemoveElem;
 pub type Nodes = BTreeMap<Id, Node>;
 pub type RootNodes = Vec<Id>;
 
+/// Problem found while resolving the inputs of a config.
+/// The input concerned is removed from the node.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GraphDiagnostic {
+    #[error("{node}: can't find input {input}")]
+    UnknownInput { node: String, input: String },
+    #[error("{node}: input {input} have an unauthorized node type: {node_type:?}")]
+    WrongNodeType {
+        node: String,
+        input: String,
+        node_type: NodeTypeLight,
+    },
+    /// Names of the nodes, each one using the next as input
+    #[error("cycle between nodes: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    /// Only the first node with this name can be used as input
+    #[error("several nodes are named {0}, only the first one can be used as input")]
+    DuplicateName(String),
+}
+
 #[derive(Debug)]
 pub struct AppGraph {
     pub nodes: Nodes,
     pub id_generator: IdGenerator,
     pub root_nodes: RootNodes,
+    pub emergency: Option<Emergency>,
+    /// Incremented when nodes are inserted or removed, or when their
+    /// inputs are sanitized, so `Update` know its plan is outdated
+    generation: u64,
 }
 
 impl AppGraph {
@@ -25,14 +53,21 @@ impl AppGraph {
             nodes: Nodes::new(),
             id_generator: IdGenerator::new(),
             root_nodes: Vec::new(),
+            emergency: None,
+            generation: 0,
         }
     }
 
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn insert_node(&mut self, node: Node) {
         if node.is_root() {
             self.root_nodes.push(node.id);
         }
         self.nodes.insert(node.id, node);
+        self.generation += 1;
     }
 
     pub fn remove_node(&mut self, id: Id) -> Option<Node> {
@@ -41,6 +76,7 @@ impl AppGraph {
             if node.is_root() {
                 self.root_nodes.remove_elem(|e| e == &id);
             }
+            self.generation += 1;
         }
         node
     }
@@ -69,6 +105,7 @@ impl AppGraph {
             let fan = Fan {
                 name: fan_h.name.clone(),
                 hardware_id: Some(fan_h.hardware_id.clone()),
+                filter: Default::default(),
                 fan_h: Some(fan_h.clone()),
             };
 
@@ -84,6 +121,7 @@ impl AppGraph {
             let temp = Temp {
                 name: temp_h.name.clone(),
                 hardware_id: Some(temp_h.hardware_id.clone()),
+                filter: Default::default(),
                 temp_h: Some(temp_h.clone()),
             };
 
@@ -95,55 +133,87 @@ impl AppGraph {
             app_graph.insert_node(node);
         }
 
-        app_graph
-    }
-
-    pub fn from_config(config: Config, hardware: &Hardware) -> Self {
-        let mut app_graph = AppGraph::new();
-
-        // order: fan -> temp -> custom_temp -> behavior -> control
-
-        for fan in config.fans {
-            let node = fan.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
-        }
+        for metric_h in &hardware.metrics {
+            let metric = Metric {
+                name: metric_h.name.clone(),
+                hardware_id: Some(metric_h.hardware_id.clone()),
+                metric_h: Some(metric_h.clone()),
+            };
 
-        for temp in config.temps {
-            let node = temp.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
+            let node = Node::new(
+                &mut app_graph.id_generator,
+                NodeType::Metric(metric),
+                &app_graph.nodes,
+            );
             app_graph.insert_node(node);
         }
 
-        for custom_temp in config.custom_temps {
-            let node = custom_temp.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
-        }
+        app_graph
+    }
 
-        for flat in config.flats {
-            let node = flat.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
-        }
+    pub fn from_config(config: Config, hardware: &Hardware) -> Self {
+        let (app_graph, diagnostics) = Self::from_config_with_diagnostics(config, hardware);
 
-        for linear in config.linears {
-            let node = linear.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
+        for diagnostic in diagnostics {
+            warn!("{}", diagnostic);
         }
+        app_graph
+    }
 
-        for target in config.targets {
-            let node = target.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
+    /// Build the graph whatever the order of the nodes in the config,
+    /// and report the inputs which can't be used.
+    pub fn from_config_with_diagnostics(
+        config: Config,
+        hardware: &Hardware,
+    ) -> (Self, Vec<GraphDiagnostic>) {
+        let mut app_graph = AppGraph::new();
+        app_graph.emergency = config.emergency.map(Emergency::sanitize);
+
+        let node_types = config
+            .fans
+            .into_iter()
+            .map(NodeType::Fan)
+            .chain(config.temps.into_iter().map(NodeType::Temp))
+            .chain(config.metrics.into_iter().map(NodeType::Metric))
+            .chain(config.custom_temps.into_iter().map(NodeType::CustomTemp))
+            .chain(config.filters.into_iter().map(NodeType::Filter))
+            .chain(config.deltas.into_iter().map(NodeType::Delta))
+            .chain(config.flats.into_iter().map(NodeType::Flat))
+            .chain(config.expressions.into_iter().map(|expression| {
+                // the inputs are read from the parsed expression
+                NodeType::Expression(Expression::new(expression.name, expression.expression))
+            }))
+            .chain(config.linears.into_iter().map(NodeType::Linear))
+            .chain(config.targets.into_iter().map(NodeType::Target))
+            .chain(config.pids.into_iter().map(NodeType::Pid))
+            .chain(config.graphs.into_iter().map(NodeType::Graph))
+            .chain(config.mixs.into_iter().map(NodeType::Mix))
+            .chain(config.switches.into_iter().map(NodeType::Switch))
+            .chain(config.schedules.into_iter().map(NodeType::Schedule))
+            .chain(config.controls.into_iter().map(NodeType::Control))
+            .collect::<Vec<_>>();
+
+        let (inputs, mut diagnostics) = resolve_inputs(&node_types);
+        let (order, cycles) = dependency_order(&inputs);
+
+        // the node closing a cycle is inserted before its input, so
+        // `Node::new` can't find it and remove it
+        for cycle in cycles {
+            let names = cycle.iter().map(|i| node_types[*i].name().clone());
+            diagnostics.push(GraphDiagnostic::Cycle(names.collect()));
         }
 
-        for graph in config.graphs {
-            let node = graph.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
-            app_graph.insert_node(node);
-        }
+        let mut node_types = node_types.into_iter().map(Some).collect::<Vec<_>>();
 
-        for control in config.controls {
-            let node = control.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
+        for index in order {
+            let Some(node_type) = node_types[index].take() else {
+                continue;
+            };
+            let node = node_type.to_node(&mut app_graph.id_generator, &app_graph.nodes, hardware);
             app_graph.insert_node(node);
         }
 
-        app_graph
+        (app_graph, diagnostics)
     }
 
     pub fn generate_default_name(&self, node_type: NodeTypeLight) -> String {
@@ -151,11 +221,19 @@ impl AppGraph {
             NodeTypeLight::Control => fl!("default_control"),
             NodeTypeLight::Fan => fl!("default_fan"),
             NodeTypeLight::Temp => fl!("default_temp"),
+            NodeTypeLight::Metric => fl!("default_metric"),
             NodeTypeLight::CustomTemp => fl!("default_custom_temp"),
             NodeTypeLight::Graph => fl!("default_graph"),
             NodeTypeLight::Flat => fl!("default_flat"),
             NodeTypeLight::Linear => fl!("default_linear"),
             NodeTypeLight::Target => fl!("default_target"),
+            NodeTypeLight::Pid => fl!("default_pid"),
+            NodeTypeLight::Filter => fl!("default_filter"),
+            NodeTypeLight::Delta => fl!("default_delta"),
+            NodeTypeLight::Expression => fl!("default_expression"),
+            NodeTypeLight::Mix => fl!("default_mix"),
+            NodeTypeLight::Switch => fl!("default_switch"),
+            NodeTypeLight::Schedule => fl!("default_schedule"),
         };
 
         fn find_unused_name(nodes: &Nodes, default_name: &str, i: u32) -> String {
@@ -175,11 +253,19 @@ impl AppGraph {
             NodeTypeLight::Control => NodeType::Control(Default::default()),
             NodeTypeLight::Fan => NodeType::Fan(Default::default()),
             NodeTypeLight::Temp => NodeType::Temp(Default::default()),
+            NodeTypeLight::Metric => NodeType::Metric(Default::default()),
             NodeTypeLight::CustomTemp => NodeType::CustomTemp(Default::default()),
             NodeTypeLight::Graph => NodeType::Graph(Default::default()),
             NodeTypeLight::Flat => NodeType::Flat(Default::default()),
             NodeTypeLight::Linear => NodeType::Linear(Default::default()),
             NodeTypeLight::Target => NodeType::Target(Default::default()),
+            NodeTypeLight::Pid => NodeType::Pid(Default::default()),
+            NodeTypeLight::Filter => NodeType::Filter(Default::default()),
+            NodeTypeLight::Delta => NodeType::Delta(Default::default()),
+            NodeTypeLight::Expression => NodeType::Expression(Default::default()),
+            NodeTypeLight::Mix => NodeType::Mix(Default::default()),
+            NodeTypeLight::Switch => NodeType::Switch(Default::default()),
+            NodeTypeLight::Schedule => NodeType::Schedule(Default::default()),
         };
 
         let new_name = self.generate_default_name(node_type_light);
@@ -199,6 +285,7 @@ impl AppGraph {
             let node = self.get_mut(&inputs.id);
             node.set_inputs(inputs);
         }
+        self.generation += 1;
     }
 
     pub fn get(&self, id: &Id) -> &Node {
@@ -213,3 +300,353 @@ impl AppGraph {
             .unwrap_or_else(|| panic!("can't find node {id} as ref mut in nodes"))
     }
 }
+
+/// The node `node_id` use `dep_id` as input, directly or through other
+/// nodes. A node depends on itself, so it can't be its own input.
+pub fn depends_on(nodes: &Nodes, node_id: &Id, dep_id: &Id) -> bool {
+    fn rec(nodes: &Nodes, node_id: &Id, dep_id: &Id, visited: &mut HashSet<Id>) -> bool {
+        if node_id == dep_id {
+            return true;
+        }
+
+        if !visited.insert(*node_id) {
+            return false;
+        }
+
+        nodes.get(node_id).is_some_and(|node| {
+            node.inputs
+                .iter()
+                .any(|input| rec(nodes, &input.id, dep_id, visited))
+        })
+    }
+
+    rec(nodes, node_id, dep_id, &mut HashSet::new())
+}
+
+/// Index of the inputs of each node, by name. The inputs which can't
+/// be used are reported and ignored.
+fn resolve_inputs(node_types: &[NodeType]) -> (Vec<Vec<usize>>, Vec<GraphDiagnostic>) {
+    let mut indexes = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for (index, node_type) in node_types.iter().enumerate() {
+        let name = node_type.name();
+        if indexes.contains_key(name.as_str()) {
+            diagnostics.push(GraphDiagnostic::DuplicateName(name.clone()));
+            continue;
+        }
+        indexes.insert(name.as_str(), index);
+    }
+
+    let inputs = node_types
+        .iter()
+        .map(|node_type| {
+            let mut inputs = Vec::new();
+
+            for (slot, name) in node_type.get_inputs().into_iter().enumerate() {
+                let Some(name) = name else {
+                    continue;
+                };
+
+                let Some(index) = indexes.get(name.as_str()) else {
+                    diagnostics.push(GraphDiagnostic::UnknownInput {
+                        node: node_type.name().clone(),
+                        input: name,
+                    });
+                    continue;
+                };
+
+                let input_type = node_types[*index].to_light();
+                if !node_type.allowed_dep_at(slot).contains(&input_type) {
+                    diagnostics.push(GraphDiagnostic::WrongNodeType {
+                        node: node_type.name().clone(),
+                        input: name,
+                        node_type: input_type,
+                    });
+                    continue;
+                }
+
+                inputs.push(*index);
+            }
+            inputs
+        })
+        .collect();
+
+    (inputs, diagnostics)
+}
+
+/// Order the nodes so that each one come after its inputs, keeping their
+/// order otherwise. `inputs[i]` contains the indexes of the inputs of the node `i`.
+/// Also return the cycles found, which are ignored by the order.
+fn dependency_order(inputs: &[Vec<usize>]) -> (Vec<usize>, Vec<Vec<usize>>) {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        New,
+        InProgress,
+        Done,
+    }
+
+    fn visit(
+        index: usize,
+        inputs: &[Vec<usize>],
+        states: &mut [State],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        states[index] = State::InProgress;
+        path.push(index);
+
+        for input in &inputs[index] {
+            match states[*input] {
+                State::New => visit(*input, inputs, states, path, order, cycles),
+                State::InProgress => {
+                    let start = path.iter().position(|i| i == input).unwrap_or_default();
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(*input);
+                    cycles.push(cycle);
+                }
+                State::Done => {}
+            }
+        }
+
+        path.pop();
+        states[index] = State::Done;
+        order.push(index);
+    }
+
+    let mut states = vec![State::New; inputs.len()];
+    let mut path = Vec::new();
+    let mut order = Vec::with_capacity(inputs.len());
+    let mut cycles = Vec::new();
+
+    for index in 0..inputs.len() {
+        if states[index] == State::New {
+            visit(
+                index,
+                inputs,
+                &mut states,
+                &mut path,
+                &mut order,
+                &mut cycles,
+            );
+        }
+    }
+
+    (order, cycles)
+}
+
+#[cfg(test)]
+mod test {
+    use hardware::Hardware;
+
+    use crate::{
+        config::{
+            delta::Delta, filter::Filter, flat::Flat, graph::Graph, metric::Metric, mix::Mix,
+            switch::Switch, temp::Temp, Config,
+        },
+        node::{NodeTypeLight, ValueKind},
+        utils::init_test_logging,
+    };
+
+    use super::{dependency_order, depends_on, AppGraph, GraphDiagnostic};
+
+    #[test]
+    fn test_dependency_order() {
+        init_test_logging();
+
+        // 0 use 2, which use 1
+        let (order, cycles) = dependency_order(&[vec![2], vec![], vec![1], vec![]]);
+        assert!(order == vec![1, 2, 0, 3]);
+        assert!(cycles.is_empty());
+
+        // 1 -> 2 -> 3 -> 1
+        let (order, cycles) = dependency_order(&[vec![], vec![2], vec![3], vec![1, 0]]);
+        assert!(order == vec![0, 3, 2, 1]);
+        assert!(cycles == vec![vec![1, 2, 3, 1]]);
+
+        let (_, cycles) = dependency_order(&[vec![0]]);
+        assert!(cycles == vec![vec![0, 0]]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        init_test_logging();
+
+        let graph = |name: &str, input: &str| Graph {
+            name: name.into(),
+            input: Some(input.into()),
+            ..Default::default()
+        };
+
+        let config = Config {
+            temps: vec![Temp {
+                name: "cpu".into(),
+                ..Default::default()
+            }],
+            flats: vec![
+                Flat {
+                    name: "flat".into(),
+                    value: 50,
+                },
+                // the temp keep the name
+                Flat {
+                    name: "cpu".into(),
+                    value: 50,
+                },
+            ],
+            graphs: vec![
+                graph("graph", "cpu"),
+                graph("unknown", "gpu"),
+                graph("wrong", "flat"),
+            ],
+            mixs: vec![Mix {
+                name: "mix".into(),
+                inputs: vec!["graph".into(), "flat".into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let (app_graph, diagnostics) =
+            AppGraph::from_config_with_diagnostics(config, &Hardware::default());
+
+        assert!(
+            diagnostics
+                == vec![
+                    GraphDiagnostic::DuplicateName("cpu".into()),
+                    GraphDiagnostic::UnknownInput {
+                        node: "unknown".into(),
+                        input: "gpu".into(),
+                    },
+                    GraphDiagnostic::WrongNodeType {
+                        node: "wrong".into(),
+                        input: "flat".into(),
+                        node_type: NodeTypeLight::Flat,
+                    },
+                ]
+        );
+
+        let node = |name: &str| {
+            app_graph
+                .nodes
+                .values()
+                .find(|node| node.name() == name)
+                .unwrap()
+        };
+
+        assert!(node("graph").inputs.len() == 1);
+        assert!(node("mix").inputs.len() == 2);
+        assert!(node("unknown").inputs.is_empty());
+        assert!(node("wrong").node_type.get_inputs() == vec![None]);
+    }
+
+    #[test]
+    fn test_mix_switch_cycle() {
+        init_test_logging();
+
+        let config = Config {
+            flats: vec![Flat {
+                name: "flat".into(),
+                value: 50,
+            }],
+            mixs: vec![Mix {
+                name: "mix".into(),
+                inputs: vec!["switch".into(), "flat".into()],
+                ..Default::default()
+            }],
+            switches: vec![Switch {
+                name: "switch".into(),
+                condition: None,
+                threshold: 60,
+                hysteresis: 0,
+                input_above: Some("mix".into()),
+                input_below: Some("flat".into()),
+            }],
+            ..Default::default()
+        };
+
+        let (app_graph, diagnostics) =
+            AppGraph::from_config_with_diagnostics(config, &Hardware::default());
+
+        assert!(
+            diagnostics
+                == vec![GraphDiagnostic::Cycle(vec![
+                    "mix".into(),
+                    "switch".into(),
+                    "mix".into()
+                ])]
+        );
+
+        let node = |name: &str| {
+            app_graph
+                .nodes
+                .values()
+                .find(|node| node.name() == name)
+                .unwrap()
+        };
+
+        // the switch closing the cycle lose its input
+        assert!(node("mix").inputs.len() == 2);
+        assert!(node("switch").inputs.len() == 1);
+
+        let depends_on = |node_name: &str, dep_name: &str| {
+            depends_on(&app_graph.nodes, &node(node_name).id, &node(dep_name).id)
+        };
+
+        // so the mix can't be picked by the switch again
+        assert!(depends_on("mix", "switch"));
+        assert!(depends_on("mix", "flat"));
+        assert!(depends_on("mix", "mix"));
+        assert!(!depends_on("switch", "mix"));
+        assert!(!depends_on("flat", "mix"));
+    }
+
+    #[test]
+    fn test_value_kind() {
+        init_test_logging();
+
+        let temp = |name: &str| Temp {
+            name: name.into(),
+            ..Default::default()
+        };
+        let filter = |name: &str, input: &str| Filter {
+            name: name.into(),
+            input: Some(input.into()),
+            ..Default::default()
+        };
+
+        let config = Config {
+            temps: vec![temp("cpu"), temp("gpu")],
+            metrics: vec![Metric {
+                name: "load".into(),
+                hardware_id: None,
+                metric_h: None,
+            }],
+            filters: vec![filter("cpu filter", "cpu"), filter("load filter", "load")],
+            deltas: vec![Delta {
+                name: "delta".into(),
+                input: Some("cpu".into()),
+                reference: Some("gpu".into()),
+            }],
+            ..Default::default()
+        };
+        let app_graph = AppGraph::from_config(config, &Hardware::default());
+
+        let kind = |name: &str| {
+            app_graph
+                .nodes
+                .values()
+                .find(|node| node.name() == name)
+                .unwrap()
+                .value_kind(&app_graph.nodes)
+        };
+
+        assert!(kind("cpu") == ValueKind::Celsius);
+        assert!(kind("load") == ValueKind::Load);
+        assert!(kind("delta") == ValueKind::TempDelta);
+        // a filter keep the kind of its input
+        assert!(kind("cpu filter") == ValueKind::Celsius);
+        assert!(kind("load filter") == ValueKind::Load);
+    }
+}
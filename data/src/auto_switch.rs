@@ -0,0 +1,445 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Rules to change the config automatically, evaluated at each update
+/// of the cli and the UI. Like when choosing another config in the UI,
+/// unsaved changes are lost on a switch.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AutoSwitch {
+    /// Config used when no rule match. The current one is kept if None.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Time (s) the rules must ask for a config before switching to it
+    #[serde(default = "default_debounce")]
+    pub debounce: u64,
+    /// Time (s) between two reads of the processes and the power source
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    /// The first rule that match win
+    #[serde(default)]
+    pub rules: Vec<ProcessRule>,
+    /// Config used on AC, when no rule match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ac: Option<String>,
+    /// Config used on battery, when no rule match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_battery: Option<String>,
+}
+
+/// Use `config` while one of `processes` is running.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProcessRule {
+    /// Names of the processes, as in `/proc/<pid>/comm`
+    pub processes: Vec<String>,
+    pub config: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+fn default_debounce() -> u64 {
+    10
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// The kernel truncate the name of the processes to 15 bytes.
+const COMM_LEN: usize = 15;
+
+impl ProcessRule {
+    pub fn is_matching(&self, running: &HashSet<String>) -> bool {
+        self.processes
+            .iter()
+            .any(|name| running.contains(name.get(..COMM_LEN).unwrap_or(name)))
+    }
+}
+
+impl AutoSwitch {
+    /// Config the rules ask for: the process rules come first, then the
+    /// power source, then the default. Configs that don't exist are ignored.
+    pub fn wanted(
+        &self,
+        running: &HashSet<String>,
+        power_source: Option<PowerSource>,
+        exists: impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        let on_power_source = match power_source {
+            Some(PowerSource::Ac) => &self.on_ac,
+            Some(PowerSource::Battery) => &self.on_battery,
+            None => &None,
+        };
+
+        self.rules
+            .iter()
+            .filter(|rule| exists(&rule.config))
+            .find(|rule| rule.is_matching(running))
+            .map(|rule| &rule.config)
+            .or(on_power_source.as_ref().filter(|config| exists(config)))
+            .or(self.default.as_ref().filter(|config| exists(config)))
+            .cloned()
+    }
+
+    fn use_power_source(&self) -> bool {
+        self.on_ac.is_some() || self.on_battery.is_some()
+    }
+}
+
+pub struct AutoSwitcher {
+    proc_root: PathBuf,
+    power_supply_root: PathBuf,
+    /// Config asked by the rules, and since when
+    pending: Option<(String, Instant)>,
+    last_scan: Option<Instant>,
+}
+
+impl Default for AutoSwitcher {
+    fn default() -> Self {
+        Self::new("/proc", "/sys/class/power_supply")
+    }
+}
+
+impl AutoSwitcher {
+    /// The paths are "/proc" and "/sys/class/power_supply", except in tests.
+    pub fn new(proc_root: impl Into<PathBuf>, power_supply_root: impl Into<PathBuf>) -> Self {
+        Self {
+            proc_root: proc_root.into(),
+            power_supply_root: power_supply_root.into(),
+            pending: None,
+            last_scan: None,
+        }
+    }
+
+    /// Return the config to load, once the rules asked for
+    /// it during the debounce time.
+    pub fn poll(
+        &mut self,
+        auto_switch: &AutoSwitch,
+        current_config: Option<&str>,
+        exists: impl Fn(&str) -> bool,
+        now: Instant,
+    ) -> Option<String> {
+        // reading all the processes at each update would be too costly
+        let interval = Duration::from_secs(auto_switch.interval);
+        if self
+            .last_scan
+            .is_some_and(|last_scan| now.duration_since(last_scan) < interval)
+        {
+            return None;
+        }
+        self.last_scan = Some(now);
+
+        let running = if auto_switch.rules.is_empty() {
+            HashSet::new()
+        } else {
+            running_processes(&self.proc_root)
+        };
+
+        let power_source = if auto_switch.use_power_source() {
+            power_source(&self.power_supply_root)
+        } else {
+            None
+        };
+
+        let wanted = auto_switch.wanted(&running, power_source, exists);
+
+        self.debounce(
+            wanted,
+            current_config,
+            Duration::from_secs(auto_switch.debounce),
+            now,
+        )
+    }
+
+    fn debounce(
+        &mut self,
+        wanted: Option<String>,
+        current_config: Option<&str>,
+        debounce: Duration,
+        now: Instant,
+    ) -> Option<String> {
+        let Some(wanted) = wanted.filter(|wanted| Some(wanted.as_str()) != current_config) else {
+            self.pending = None;
+            return None;
+        };
+
+        let since = match &self.pending {
+            Some((pending, since)) if pending == &wanted => *since,
+            _ => now,
+        };
+
+        if now.duration_since(since) >= debounce {
+            self.pending = None;
+            Some(wanted)
+        } else {
+            self.pending = Some((wanted, since));
+            None
+        }
+    }
+}
+
+/// Names of the running processes, from `/proc/<pid>/comm`
+fn running_processes(proc_root: &Path) -> HashSet<String> {
+    let entries = match fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("Can't list the processes: {}.", e);
+            return HashSet::new();
+        }
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        // the process can end while we read it
+        .filter_map(|entry| fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_owned())
+        .collect()
+}
+
+/// None if the system has no battery, or if it can't be known.
+fn power_source(power_supply_root: &Path) -> Option<PowerSource> {
+    let entries = match fs::read_dir(power_supply_root) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("Can't list the power supplies: {}.", e);
+            return None;
+        }
+    };
+
+    let read = |path: &Path, file: &str| {
+        fs::read_to_string(path.join(file))
+            .map(|content| content.trim().to_owned())
+            .unwrap_or_default()
+    };
+
+    let mut external_online = None;
+    let mut discharging = None;
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+
+        match read(&path, "type").as_str() {
+            // usb for type-c chargers
+            "Mains" | "USB" => {
+                let online = read(&path, "online") == "1";
+                external_online = Some(external_online.unwrap_or(false) || online);
+            }
+            // batteries of a mouse or a keyboard have the scope "Device"
+            "Battery" if read(&path, "scope") != "Device" => {
+                let is_discharging = read(&path, "status") == "Discharging";
+                discharging = Some(discharging.unwrap_or(false) || is_discharging);
+            }
+            _ => {}
+        }
+    }
+
+    match (external_online, discharging) {
+        (Some(true), _) => Some(PowerSource::Ac),
+        (Some(false), _) => Some(PowerSource::Battery),
+        (None, Some(true)) => Some(PowerSource::Battery),
+        (None, Some(false)) => Some(PowerSource::Ac),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashSet,
+        fs,
+        time::{Duration, Instant},
+    };
+
+    use crate::utils::init_test_logging;
+
+    use super::{
+        power_source, running_processes, AutoSwitch, AutoSwitcher, PowerSource, ProcessRule,
+    };
+
+    #[test]
+    fn test_wanted() {
+        init_test_logging();
+
+        let auto_switch = AutoSwitch {
+            default: Some("quiet".into()),
+            rules: vec![
+                ProcessRule {
+                    processes: vec!["blender".into(), "cargo".into()],
+                    config: "performance".into(),
+                },
+                ProcessRule {
+                    processes: vec!["a-very-long-process-name".into()],
+                    config: "missing".into(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let exists = |name: &str| name != "missing";
+        let running = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<HashSet<_>>();
+
+        assert!(
+            auto_switch.wanted(&running(&["bash", "cargo"]), None, exists)
+                == Some("performance".into())
+        );
+        assert!(auto_switch.wanted(&running(&["bash"]), None, exists) == Some("quiet".into()));
+        assert!(
+            auto_switch.wanted(&running(&["a-very-long-pro"]), None, exists)
+                == Some("quiet".into())
+        );
+        assert!(
+            auto_switch.wanted(&running(&["a-very-long-pro"]), None, |_| true)
+                == Some("missing".into())
+        );
+    }
+
+    #[test]
+    fn test_debounce() {
+        init_test_logging();
+
+        let mut switcher = AutoSwitcher::new("", "");
+        let debounce = Duration::from_secs(10);
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let performance = || Some("performance".to_owned());
+
+        assert!(switcher
+            .debounce(performance(), Some("quiet"), debounce, at(0))
+            .is_none());
+        assert!(switcher
+            .debounce(performance(), Some("quiet"), debounce, at(5))
+            .is_none());
+        // the process ended before the debounce time
+        assert!(switcher
+            .debounce(None, Some("quiet"), debounce, at(6))
+            .is_none());
+        assert!(switcher
+            .debounce(performance(), Some("quiet"), debounce, at(7))
+            .is_none());
+        assert!(switcher.debounce(performance(), Some("quiet"), debounce, at(17)) == performance());
+        assert!(switcher
+            .debounce(performance(), Some("performance"), debounce, at(18))
+            .is_none());
+    }
+
+    #[test]
+    fn test_running_processes() {
+        init_test_logging();
+
+        let proc_root =
+            std::env::temp_dir().join(format!("fan-control-processes-{}", std::process::id()));
+
+        for (pid, comm) in [("1", "systemd\n"), ("42", "cargo\n"), ("self", "bash\n")] {
+            fs::create_dir_all(proc_root.join(pid)).unwrap();
+            fs::write(proc_root.join(pid).join("comm"), comm).unwrap();
+        }
+
+        let running = running_processes(&proc_root);
+        assert!(running == HashSet::from(["systemd".to_owned(), "cargo".to_owned()]));
+
+        fs::remove_dir_all(proc_root).unwrap();
+    }
+
+    #[test]
+    fn test_interval() {
+        init_test_logging();
+
+        let proc_root =
+            std::env::temp_dir().join(format!("fan-control-interval-{}", std::process::id()));
+        fs::create_dir_all(proc_root.join("42")).unwrap();
+        fs::write(proc_root.join("42").join("comm"), "cargo\n").unwrap();
+
+        let auto_switch = AutoSwitch {
+            rules: vec![ProcessRule {
+                processes: vec!["cargo".into()],
+                config: "performance".into(),
+            }],
+            debounce: 0,
+            interval: 5,
+            ..Default::default()
+        };
+        let mut switcher = AutoSwitcher::new(&proc_root, "");
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut poll = |secs| switcher.poll(&auto_switch, Some("quiet"), |_| true, at(secs));
+
+        assert!(poll(0) == Some("performance".into()));
+        // the processes are not read again yet
+        assert!(poll(3).is_none());
+        assert!(poll(5) == Some("performance".into()));
+
+        fs::remove_dir_all(proc_root).unwrap();
+    }
+
+    #[test]
+    fn test_power_source() {
+        init_test_logging();
+
+        let root =
+            std::env::temp_dir().join(format!("fan-control-power-supply-{}", std::process::id()));
+
+        let write = |supply: &str, files: &[(&str, &str)]| {
+            fs::create_dir_all(root.join(supply)).unwrap();
+            for (file, content) in files {
+                fs::write(root.join(supply).join(file), format!("{content}\n")).unwrap();
+            }
+        };
+
+        assert!(power_source(&root).is_none());
+
+        write(
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("status", "Discharging"),
+            ],
+        );
+        assert!(power_source(&root).is_none());
+
+        write("BAT0", &[("type", "Battery"), ("status", "Discharging")]);
+        assert!(power_source(&root) == Some(PowerSource::Battery));
+
+        write("AC", &[("type", "Mains"), ("online", "0")]);
+        write(
+            "ucsi-source-psy-USBC000:001",
+            &[("type", "USB"), ("online", "1")],
+        );
+        assert!(power_source(&root) == Some(PowerSource::Ac));
+
+        let auto_switch = AutoSwitch {
+            default: Some("default".into()),
+            rules: vec![ProcessRule {
+                processes: vec!["cargo".into()],
+                config: "performance".into(),
+            }],
+            on_battery: Some("quiet".into()),
+            ..Default::default()
+        };
+
+        let running = HashSet::from(["cargo".to_owned()]);
+        let on_battery = Some(PowerSource::Battery);
+
+        assert!(auto_switch.wanted(&running, on_battery, |_| true) == Some("performance".into()));
+        assert!(auto_switch.wanted(&HashSet::new(), on_battery, |_| true) == Some("quiet".into()));
+        assert!(
+            auto_switch.wanted(&HashSet::new(), Some(PowerSource::Ac), |_| true)
+                == Some("default".into())
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
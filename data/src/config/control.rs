@@ -1,6 +1,9 @@
-use std::rc::Rc;
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use hardware::{HControl, Hardware, HardwareBridge, Mode, Value};
+use hardware::{HControl, HSensor, Hardware, HardwareBridge, Mode, Value};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -10,6 +13,8 @@ use crate::{
     update::UpdateError,
 };
 
+use super::utils::spike_filter::{SpikeFilter, SpikeFilterState};
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Control {
     pub name: String,
@@ -17,6 +22,67 @@ pub struct Control {
     pub hardware_id: Option<String>,
     pub input: Option<String>,
     pub active: bool,
+    /// Other hardware controls driven by the same behavior
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Member>,
+    /// Lowest value (%) written, whatever the behavior
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<u8>,
+    /// Highest value (%) written, whatever the behavior
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u8>,
+    /// Value (%) written during an emergency, 100 if None.
+    /// `min` and `max` don't apply to it.
+    #[serde(
+        default,
+        rename = "emergencyValue",
+        alias = "emergency_value",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub emergency_value: Option<u8>,
+    /// Maximum increase of the output, in % per second
+    #[serde(
+        default,
+        rename = "rampUp",
+        alias = "ramp_up",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ramp_up: Option<u8>,
+    /// Maximum decrease of the output, in % per second
+    #[serde(
+        default,
+        rename = "rampDown",
+        alias = "ramp_down",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ramp_down: Option<u8>,
+    /// Below this value (%), the fan is stopped
+    #[serde(
+        default,
+        rename = "stopBelow",
+        alias = "stop_below",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stop_below: Option<u8>,
+    /// Minimum value (%) while the fan is running
+    #[serde(
+        default,
+        rename = "minDuty",
+        alias = "min_duty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_duty: Option<u8>,
+    /// Kick applied when the fan start from a stop
+    #[serde(
+        default,
+        rename = "startBoost",
+        alias = "start_boost",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_boost: Option<StartBoost>,
+    /// The value of the behavior is a percentage of a speed in RPM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<RpmTarget>,
 
     #[serde(skip)]
     pub control_h: Option<Rc<HControl>>,
@@ -25,6 +91,145 @@ pub struct Control {
     pub mode_set: Option<Mode>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ControlState {
+    /// Last output and when it was computed. A float is kept
+    /// so slow ramps are not lost in rounding.
+    pub last_output: Option<(f32, Instant)>,
+    pub boost_until: Option<Instant>,
+    /// Last duty written for a `RpmTarget` and when
+    pub duty: Option<(f32, Instant)>,
+    /// Last value written by the behavior, so it is not written again
+    pub written: Option<Value>,
+    /// Filter of the speed measured for a `RpmTarget`
+    pub fan: SpikeFilterState,
+}
+
+impl ControlState {
+    /// Value written without going through `Control::output`,
+    /// the ramp will start from it.
+    pub fn reset_output(&mut self, value: Value, now: Instant) {
+        self.last_output = Some((value as f32, now));
+        self.duty = Some((value as f32, now));
+    }
+}
+
+/// A hardware control that follow the value of the `Control`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Member {
+    #[serde(rename = "id")]
+    pub hardware_id: String,
+    /// Multiply the value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    /// Added to the value (%), after the scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i16>,
+
+    #[serde(skip)]
+    pub control_h: Option<Rc<HControl>>,
+}
+
+impl Member {
+    /// Value (%) written to this member. A stopped fan stay stopped.
+    pub fn value(&self, value: Value) -> Value {
+        if value <= 0 {
+            return 0;
+        }
+
+        let value = match self.scale {
+            Some(scale) => (value as f32 * scale).round() as Value,
+            None => value,
+        };
+
+        let value = match self.offset {
+            Some(offset) => value + Value::from(offset),
+            None => value,
+        };
+
+        value.clamp(0, 100)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StartBoost {
+    /// Value (%) of the kick
+    pub speed: u8,
+    /// Duration of the kick, in seconds
+    pub duration: u16,
+}
+
+/// Reach a speed in RPM, instead of writing a duty. Without
+/// hardware support, the members follow the duty of the main fan.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RpmTarget {
+    /// Hardware id of the fan driven by this control. Not needed
+    /// when the driver regulate the speed itself.
+    #[serde(
+        default,
+        rename = "fanId",
+        alias = "fan_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fan_id: Option<String>,
+    /// Speed reached at 100%
+    #[serde(rename = "maxRpm", alias = "max_rpm")]
+    pub max_rpm: u16,
+    /// Change of the duty (%) per second, for an error of 1% of `max_rpm`
+    #[serde(default = "default_gain")]
+    pub gain: f32,
+
+    #[serde(skip)]
+    pub fan_h: Option<Rc<HSensor>>,
+    /// Spike filter of the `Fan` reading the same hardware, if any
+    #[serde(skip)]
+    pub fan_filter: SpikeFilter,
+}
+
+fn default_gain() -> f32 {
+    0.5
+}
+
+impl RpmTarget {
+    /// Speed to reach for a value (%) of the behavior.
+    pub fn target(&self, value: Value) -> Value {
+        (f32::from(self.max_rpm) * value as f32 / 100.0).round() as Value
+    }
+
+    /// Duty to write so the measured speed converge to the target.
+    pub fn duty(
+        &self,
+        state: &mut ControlState,
+        value: Value,
+        measured: Value,
+        now: Instant,
+    ) -> Value {
+        let duty = match state.duty {
+            _ if value <= 0 => 0.0,
+            Some((last, last_time)) => {
+                let elapsed = now.duration_since(last_time).as_secs_f32();
+                let error =
+                    (self.target(value) - measured) as f32 / f32::from(self.max_rpm) * 100.0;
+                (last + self.gain * error * elapsed).clamp(0.0, 100.0)
+            }
+            // the value is a good first guess
+            None => value as f32,
+        };
+
+        state.duty = Some((duty, now));
+        duty.round() as Value
+    }
+}
+
+impl Default for StartBoost {
+    fn default() -> Self {
+        Self {
+            speed: 100,
+            duration: 2,
+        }
+    }
+}
+
 impl Control {
     pub fn new(
         name: String,
@@ -38,27 +243,244 @@ impl Control {
             hardware_id,
             input,
             active,
+            members: Vec::new(),
+            min: None,
+            max: None,
+            emergency_value: None,
+            ramp_up: None,
+            ramp_down: None,
+            stop_below: None,
+            min_duty: None,
+            start_boost: None,
+            rpm: None,
             control_h,
             mode_set: None,
         }
     }
 
+    /// Compute the value to write from the value of the behavior.
+    pub fn output(&self, state: &mut ControlState, value: Value, now: Instant) -> Value {
+        let value = self.clamp(value);
+        let target = if self.is_stop(value) { 0 } else { value };
+
+        if let Some(boost) = self.start_boost(state, target, now) {
+            state.reset_output(boost, now);
+            return boost;
+        }
+
+        let ramped = self.ramp(state, target, now);
+        let output = self.stop_or_run(target, ramped);
+
+        if output != ramped {
+            // the ramp continue from the value really written
+            state.last_output = Some((output as f32, now));
+        }
+        output
+    }
+
+    /// Keep the value between `min` and `max`, and in 0..=100 because
+    /// an expression can return any value. If they overlap, `min` win:
+    /// it protect the hardware.
+    fn clamp(&self, value: Value) -> Value {
+        let value = value.clamp(0, 100);
+
+        let value = match self.max {
+            Some(max) => value.min(max.into()),
+            None => value,
+        };
+
+        match self.min {
+            Some(min) => value.max(min.into()),
+            None => value,
+        }
+    }
+
+    /// A value of 0 always stop the fan.
+    fn is_stop(&self, value: Value) -> bool {
+        value <= 0 || self.stop_below.is_some_and(|stop| value < stop.into())
+    }
+
+    /// Below this value (%), the fan can stall.
+    fn stall_below(&self) -> Value {
+        self.min_duty.max(self.stop_below).map_or(0, Value::from)
+    }
+
+    /// Keep the ramped value out of the stall zone: when the fan
+    /// is stopping, it jump to 0 once the value is in it, otherwise
+    /// the value is kept above it.
+    fn stop_or_run(&self, target: Value, ramped: Value) -> Value {
+        if target == 0 {
+            if ramped < self.stall_below() {
+                0
+            } else {
+                ramped
+            }
+        } else {
+            ramped.max(self.stall_below())
+        }
+    }
+
+    fn is_stopped(state: &ControlState) -> bool {
+        !matches!(state.last_output, Some((last, _)) if last.round() > 0.0)
+    }
+
+    /// Return the value of the kick, while it is applied.
+    fn start_boost(&self, state: &mut ControlState, value: Value, now: Instant) -> Option<Value> {
+        let start_boost = self.start_boost.as_ref()?;
+
+        if value == 0 {
+            state.boost_until = None;
+            return None;
+        }
+
+        if state.boost_until.is_none() && Self::is_stopped(state) {
+            let duration = Duration::from_secs(start_boost.duration.into());
+            state.boost_until = Some(now + duration);
+        }
+
+        match state.boost_until {
+            Some(boost_until) if now < boost_until => Some(self.clamp(start_boost.speed.into())),
+            _ => {
+                state.boost_until = None;
+                None
+            }
+        }
+    }
+
+    fn ramp(&self, state: &mut ControlState, value: Value, now: Instant) -> Value {
+        let output = match state.last_output {
+            Some((last, last_time)) => {
+                let elapsed = now.duration_since(last_time).as_secs_f32();
+                let delta = value as f32 - last;
+
+                let max_delta = if delta > 0.0 {
+                    self.ramp_up
+                } else {
+                    self.ramp_down
+                };
+
+                match max_delta {
+                    Some(max_delta) => {
+                        let max_delta = f32::from(max_delta) * elapsed;
+                        last + delta.clamp(-max_delta, max_delta)
+                    }
+                    None => value as f32,
+                }
+            }
+            None => value as f32,
+        };
+
+        state.last_output = Some((output, now));
+        output.round() as Value
+    }
+
+    /// Write a value computed by `output`, as a duty or as a speed.
+    pub fn apply<H: HardwareBridge>(
+        &mut self,
+        state: &mut ControlState,
+        value: Value,
+        now: Instant,
+        bridge: &mut H,
+    ) -> Result<Value, UpdateError> {
+        if self.rpm.is_none() {
+            return self.set_value(value, bridge);
+        }
+
+        if self.uses_rpm_target() {
+            return self.set_rpm_target(value, bridge);
+        }
+
+        let Some(rpm) = &self.rpm else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+        let Some(fan_h) = &rpm.fan_h else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+        let measured = bridge.get_sensor_value(fan_h)?;
+        let measured = rpm.fan_filter.filter(&mut state.fan, measured, now)?;
+        let duty = rpm.duty(state, value, measured, now);
+
+        self.set_value(duty, bridge)
+    }
+
+    /// The driver of every control regulate the speed itself.
+    fn uses_rpm_target(&self) -> bool {
+        let rpm_target = |control_h: &Option<Rc<HControl>>| {
+            control_h
+                .as_ref()
+                .is_some_and(|control_h| control_h.rpm_target)
+        };
+
+        rpm_target(&self.control_h)
+            && self
+                .members
+                .iter()
+                .all(|m| m.control_h.is_none() || rpm_target(&m.control_h))
+    }
+
+    fn set_rpm_target<H: HardwareBridge>(
+        &mut self,
+        value: Value,
+        bridge: &mut H,
+    ) -> Result<Value, UpdateError> {
+        if self.mode_set != Some(Mode::Rpm) {
+            self.set_mode(Mode::Rpm, bridge)?;
+        }
+
+        let (Some(rpm), Some(control_h)) = (&self.rpm, &self.control_h) else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+
+        bridge.set_rpm_target(control_h, rpm.target(value))?;
+        for member in &self.members {
+            if let Some(control_h) = &member.control_h {
+                bridge.set_rpm_target(control_h, rpm.target(member.value(value)))?;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Write the value, with the offset and scale of each member.
     pub fn set_value<H: HardwareBridge>(
         &mut self,
         value: Value,
         bridge: &mut H,
+    ) -> Result<Value, UpdateError> {
+        self.write(value, bridge, Member::value)
+    }
+
+    /// Write the same value to every member, whatever its offset and scale.
+    pub fn set_value_to_all<H: HardwareBridge>(
+        &mut self,
+        value: Value,
+        bridge: &mut H,
+    ) -> Result<Value, UpdateError> {
+        self.write(value, bridge, |_, value| value)
+    }
+
+    fn write<H: HardwareBridge>(
+        &mut self,
+        value: Value,
+        bridge: &mut H,
+        member_value: impl Fn(&Member, Value) -> Value,
     ) -> Result<Value, UpdateError> {
         if self.mode_set != Some(Mode::Manual) {
             self.set_mode(Mode::Manual, bridge)?;
         }
 
-        match &self.control_h {
-            Some(control_h) => {
-                bridge.set_value(control_h, value)?;
-                Ok(value)
+        let Some(control_h) = &self.control_h else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+
+        bridge.set_value(control_h, value)?;
+        for member in &self.members {
+            if let Some(control_h) = &member.control_h {
+                bridge.set_value(control_h, member_value(member, value))?;
             }
-            None => Err(UpdateError::NodeIsInvalid(self.name.clone())),
         }
+
+        Ok(value)
     }
 
     pub fn set_mode<H: HardwareBridge>(
@@ -78,11 +500,49 @@ impl Control {
             None => return Err(UpdateError::NodeIsInvalid(self.name.clone())),
         };
 
+        for member in &self.members {
+            if let Some(control_h) = &member.control_h {
+                bridge.set_mode(control_h, &mode)?;
+            }
+        }
+
         info!("Mode {} succefuly set for {}.", mode, self.name);
         self.mode_set = Some(mode);
         Ok(())
     }
 
+    pub fn add_member(&mut self, control_h: Rc<HControl>) {
+        self.members.push(Member {
+            hardware_id: control_h.hardware_id.clone(),
+            control_h: Some(control_h),
+            ..Default::default()
+        });
+
+        // the new member must be set to manual too
+        self.mode_set = None;
+    }
+
+    /// Give the control of the member back to the hardware.
+    pub fn remove_member<H: HardwareBridge>(
+        &mut self,
+        hardware_id: &str,
+        bridge: &mut H,
+    ) -> Result<(), UpdateError> {
+        let Some(index) = self
+            .members
+            .iter()
+            .position(|m| m.hardware_id == hardware_id)
+        else {
+            return Ok(());
+        };
+
+        let member = self.members.remove(index);
+        if let Some(control_h) = &member.control_h {
+            bridge.set_mode(control_h, &Mode::Auto)?;
+        }
+        Ok(())
+    }
+
     pub fn get_value<H: HardwareBridge>(&self, bridge: &mut H) -> Result<Value, UpdateError> {
         match &self.control_h {
             Some(control_h) => bridge
@@ -93,12 +553,32 @@ impl Control {
     }
 }
 
+impl Control {
+    /// The limits are percentages.
+    fn limits_are_valid(&self) -> bool {
+        [self.min, self.max, self.emergency_value]
+            .into_iter()
+            .flatten()
+            .all(|limit| limit <= 100)
+    }
+
+    fn rpm_is_valid(&self) -> bool {
+        match &self.rpm {
+            Some(rpm) => rpm.max_rpm > 0 && (self.uses_rpm_target() || rpm.fan_h.is_some()),
+            None => true,
+        }
+    }
+}
+
 impl IsValid for Control {
     fn is_valid(&self) -> bool {
         self.active
             && self.hardware_id.is_some()
             && self.control_h.is_some()
             && self.input.is_some()
+            && !matches!((self.min, self.max), (Some(min), Some(max)) if min > max)
+            && self.limits_are_valid()
+            && self.rpm_is_valid()
     }
 }
 
@@ -135,6 +615,308 @@ impl ToNode for Control {
             }
         }
 
+        let mut hardware_ids: Vec<String> = self.hardware_id.iter().cloned().collect();
+        self.members.retain_mut(|member| {
+            if hardware_ids.contains(&member.hardware_id) {
+                warn!(
+                    "Control to Node, hardware id \"{}\" is used twice by {}.",
+                    member.hardware_id, self.name
+                );
+                return false;
+            }
+            hardware_ids.push(member.hardware_id.clone());
+
+            member.control_h = hardware
+                .controls
+                .iter()
+                .find(|control_h| control_h.hardware_id == member.hardware_id)
+                .cloned();
+
+            // kept, like the main hardware id, so the config is not lost
+            if member.control_h.is_none() {
+                warn!("Control to Node, member \"{}\" was not found for {}. Fall back: member not used.", member.hardware_id, self.name);
+            }
+            true
+        });
+
+        if let Some(rpm) = &mut self.rpm {
+            if let Some(fan_id) = &rpm.fan_id {
+                rpm.fan_h = hardware
+                    .fans
+                    .iter()
+                    .find(|fan_h| &fan_h.hardware_id == fan_id)
+                    .cloned();
+
+                if rpm.fan_h.is_none() {
+                    warn!(
+                        "Control to Node, fan id \"{}\" was not found for {}.",
+                        fan_id, self.name
+                    );
+                }
+
+                // the fans are inserted before the controls
+                let fan = nodes.values().find_map(|node| match &node.node_type {
+                    NodeType::Fan(fan) if fan.hardware_id.as_ref() == Some(fan_id) => Some(fan),
+                    _ => None,
+                });
+                if let Some(fan) = fan {
+                    rpm.fan_filter = fan.filter.clone();
+                }
+            }
+        }
+
         Node::new(id_generator, NodeType::Control(self), nodes)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use hardware::{fake_hardware::FakeHardwareBridge, Hardware, HardwareBridge};
+
+    use crate::{
+        app_graph::Nodes,
+        config::{fan::Fan, utils::spike_filter::SpikeFilter},
+        id::IdGenerator,
+        node::ToNode,
+        utils::init_test_logging,
+    };
+
+    use super::{Control, ControlState, Member, RpmTarget, StartBoost};
+
+    #[test]
+    fn test_ramp() {
+        init_test_logging();
+
+        let mut control = Control {
+            ramp_up: Some(10),
+            ramp_down: Some(2),
+            ..Default::default()
+        };
+        let mut state = ControlState::default();
+
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert!(control.output(&mut state, 30, at(0)) == 30);
+        assert!(control.output(&mut state, 100, at(1000)) == 40);
+        assert!(control.output(&mut state, 100, at(1500)) == 45);
+        assert!(control.output(&mut state, 0, at(2500)) == 43);
+        // 0.5 % each 250 ms
+        assert!(control.output(&mut state, 0, at(2750)) == 43);
+        assert!(control.output(&mut state, 0, at(3000)) == 42);
+
+        control.ramp_down = None;
+        assert!(control.output(&mut state, 0, at(3250)) == 0);
+    }
+
+    #[test]
+    fn test_stop_start() {
+        init_test_logging();
+
+        let control = Control {
+            stop_below: Some(20),
+            min_duty: Some(30),
+            start_boost: Some(StartBoost {
+                speed: 100,
+                duration: 2,
+            }),
+            ..Default::default()
+        };
+        let mut state = ControlState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(control.output(&mut state, 10, at(0)) == 0);
+        // the fan start: kick for 2 seconds
+        assert!(control.output(&mut state, 25, at(1)) == 100);
+        assert!(control.output(&mut state, 25, at(2)) == 100);
+        assert!(control.output(&mut state, 25, at(3)) == 30);
+        assert!(control.output(&mut state, 50, at(4)) == 50);
+        assert!(control.output(&mut state, 19, at(5)) == 0);
+        assert!(control.output(&mut state, 0, at(6)) == 0);
+        assert!(control.output(&mut state, 60, at(7)) == 100);
+    }
+
+    #[test]
+    fn test_ramp_to_stop() {
+        init_test_logging();
+
+        let control = Control {
+            ramp_down: Some(10),
+            stop_below: Some(20),
+            min_duty: Some(30),
+            ..Default::default()
+        };
+        let mut state = ControlState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(control.output(&mut state, 60, at(0)) == 60);
+        assert!(control.output(&mut state, 0, at(1)) == 50);
+        assert!(control.output(&mut state, 10, at(2)) == 40);
+        assert!(control.output(&mut state, 0, at(3)) == 30);
+        // no value under min_duty: the fan is stopped at once
+        assert!(control.output(&mut state, 0, at(4)) == 0);
+        assert!(control.output(&mut state, 0, at(5)) == 0);
+
+        // a running fan is not ramped into the stall zone
+        assert!(control.output(&mut state, 60, at(6)) == 60);
+        assert!(control.output(&mut state, 25, at(9)) == 30);
+        assert!(control.output(&mut state, 25, at(10)) == 30);
+    }
+
+    #[test]
+    fn test_clamps() {
+        init_test_logging();
+
+        let control = Control {
+            min: Some(60),
+            max: Some(70),
+            start_boost: Some(StartBoost::default()),
+            ..Default::default()
+        };
+        let mut state = ControlState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // the kick is clamped too
+        assert!(control.output(&mut state, 0, at(0)) == 70);
+        assert!(control.output(&mut state, 30, at(3)) == 60);
+        assert!(control.output(&mut state, 65, at(4)) == 65);
+        assert!(control.output(&mut state, 100, at(5)) == 70);
+        // the fan is never stopped, so it is never kicked again
+        assert!(control.output(&mut state, 0, at(6)) == 60);
+        assert!(control.output(&mut state, 30, at(7)) == 60);
+
+        // the value of an expression is not bounded
+        let control = Control::default();
+        let mut state = ControlState::default();
+        assert!(control.output(&mut state, 180, at(8)) == 100);
+        assert!(control.output(&mut state, -20, at(9)) == 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        init_test_logging();
+
+        let control = Control {
+            min: Some(20),
+            max: Some(100),
+            ..Default::default()
+        };
+        assert!(control.limits_are_valid());
+
+        for control in [
+            Control {
+                max: Some(150),
+                ..control.clone()
+            },
+            Control {
+                emergency_value: Some(101),
+                ..control.clone()
+            },
+        ] {
+            assert!(!control.limits_are_valid());
+        }
+    }
+
+    #[test]
+    fn test_members() {
+        init_test_logging();
+
+        let member = Member {
+            hardware_id: "pwm2".into(),
+            scale: Some(0.8),
+            offset: Some(10),
+            ..Default::default()
+        };
+
+        assert!(member.value(50) == 50);
+        assert!(member.value(100) == 90);
+        assert!(member.value(0) == 0);
+
+        let member = Member {
+            offset: Some(-20),
+            ..member
+        };
+
+        assert!(member.value(10) == 0);
+        assert!(Member::default().value(42) == 42);
+
+        // a member not found is kept, without hardware
+        let control = Control {
+            name: "control".into(),
+            members: vec![member.clone(), member],
+            ..Default::default()
+        };
+        let node = control.to_node(&mut IdGenerator::new(), &Nodes::new(), &Hardware::default());
+        let members = &node.node_type.unwrap_control_ref().members;
+        assert!(members.len() == 1);
+        assert!(members[0].hardware_id == "pwm2");
+        assert!(members[0].control_h.is_none());
+    }
+
+    #[test]
+    fn test_rpm() {
+        init_test_logging();
+
+        let rpm = RpmTarget {
+            max_rpm: 2000,
+            gain: 0.5,
+            ..Default::default()
+        };
+        let mut state = ControlState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(rpm.target(50) == 1000);
+        assert!(rpm.duty(&mut state, 50, 0, at(0)) == 50);
+        // 200 RPM below the target: 10% of the max
+        assert!(rpm.duty(&mut state, 50, 800, at(1)) == 55);
+        assert!(rpm.duty(&mut state, 50, 1000, at(2)) == 55);
+        assert!(rpm.duty(&mut state, 50, 1400, at(4)) == 35);
+        assert!(rpm.duty(&mut state, 0, 1000, at(5)) == 0);
+    }
+
+    #[test]
+    fn test_rpm_fan_filter() {
+        init_test_logging();
+
+        let bridge = FakeHardwareBridge::new().unwrap();
+        let mut id_generator = IdGenerator::new();
+        let mut nodes = Nodes::new();
+
+        let fan = Fan {
+            name: "fan".into(),
+            hardware_id: Some("fan1".into()),
+            filter: SpikeFilter {
+                max: Some(5000),
+                ..Default::default()
+            },
+            fan_h: None,
+        }
+        .to_node(&mut id_generator, &nodes, bridge.hardware());
+        nodes.insert(fan.id, fan);
+
+        let control = Control {
+            rpm: Some(RpmTarget {
+                fan_id: Some("fan1".into()),
+                max_rpm: 2000,
+                ..Default::default()
+            }),
+            ..Control::new("control".into(), None, None, true, None)
+        }
+        .to_node(&mut id_generator, &nodes, bridge.hardware());
+
+        // the closed loop read the fan through the same filter
+        let rpm = control.node_type.unwrap_control_ref().rpm.as_ref().unwrap();
+        assert!(rpm.fan_h.is_some());
+        assert!(rpm.fan_filter.max == Some(5000));
+    }
+}
@@ -0,0 +1,102 @@
+use hardware::{Hardware, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Difference between two temps, `input - reference`.
+/// The order of the inputs matters, so they are stored in two slots.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Delta {
+    pub name: String,
+    pub input: Option<String>,
+    pub reference: Option<String>,
+}
+
+impl Delta {
+    /// `values` follow the order of the slots: input, then reference.
+    pub fn get_value(&self, values: &[Value]) -> Result<Value, UpdateError> {
+        match values {
+            [input, reference] => Ok(input - reference),
+            _ => Err(UpdateError::NoInputData),
+        }
+    }
+}
+
+impl IsValid for Delta {
+    fn is_valid(&self) -> bool {
+        self.input.is_some() && self.reference.is_some()
+    }
+}
+
+impl ToNode for Delta {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Delta(self), nodes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hardware::Hardware;
+
+    use crate::{
+        app_graph::Nodes,
+        config::temp::Temp,
+        id::IdGenerator,
+        node::{IsValid, NodeType, ToNode},
+        utils::init_test_logging,
+    };
+
+    use super::Delta;
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let delta = Delta {
+            name: "delta".into(),
+            input: Some("gpu".into()),
+            reference: Some("ambient".into()),
+        };
+
+        assert!(delta.get_value(&[70, 30]).unwrap() == 40);
+        assert!(delta.get_value(&[30, 70]).unwrap() == -40);
+        assert!(delta.get_value(&[70]).is_err());
+    }
+
+    #[test]
+    fn test_slots() {
+        init_test_logging();
+
+        let mut id_generator = IdGenerator::new();
+        let mut nodes = Nodes::new();
+        let hardware = Hardware::default();
+
+        let ambient = Temp {
+            name: "ambient".into(),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        nodes.insert(ambient.id, ambient);
+
+        // the input is missing, the reference must stay in its slot
+        let delta = Delta {
+            name: "delta".into(),
+            input: Some("gpu".into()),
+            reference: Some("ambient".into()),
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+
+        let NodeType::Delta(delta) = delta.node_type else {
+            panic!()
+        };
+
+        assert!(delta.input.is_none());
+        assert!(delta.reference == Some("ambient".into()));
+        assert!(!delta.is_valid());
+    }
+}
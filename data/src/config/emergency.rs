@@ -0,0 +1,127 @@
+use hardware::{HSensor, Value};
+use serde::{Deserialize, Serialize};
+
+/// Global rule, evaluated outside of the node graph. When it is triggered,
+/// all active controls are set to their emergency value (100% by default),
+/// whatever behavior they use.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Emergency {
+    #[serde(rename = "triggerTemp", alias = "trigger_temp")]
+    pub trigger_temp: u8,
+    #[serde(rename = "releaseTemp", alias = "release_temp")]
+    pub release_temp: u8,
+    /// Also trigger when a temp reach the critical limit of its driver
+    #[serde(default = "default_use_crit", rename = "useCrit", alias = "use_crit")]
+    pub use_crit: bool,
+    /// Hardware ids of the temps to watch. All temps are watched if empty.
+    #[serde(default, rename = "ids")]
+    pub hardware_ids: Vec<String>,
+}
+
+fn default_use_crit() -> bool {
+    true
+}
+
+impl Default for Emergency {
+    fn default() -> Self {
+        Self {
+            trigger_temp: 90,
+            release_temp: 80,
+            use_crit: default_use_crit(),
+            hardware_ids: Default::default(),
+        }
+    }
+}
+
+impl Emergency {
+    /// The release temp must be under the trigger temp, otherwise the
+    /// emergency would never release, or switch at each update.
+    pub fn sanitize(mut self) -> Self {
+        if self.release_temp >= self.trigger_temp {
+            let release_temp = self.trigger_temp.saturating_sub(1);
+            warn!(
+                "Emergency: release temp {} is not under trigger temp {}, {} is used.",
+                self.release_temp, self.trigger_temp, release_temp
+            );
+            self.release_temp = release_temp;
+        }
+        self
+    }
+
+    pub fn watch(&self, temp_h: &HSensor) -> bool {
+        self.hardware_ids.is_empty() || self.hardware_ids.contains(&temp_h.hardware_id)
+    }
+
+    fn reach_crit(&self, value: Value, crit: Option<Value>) -> bool {
+        self.use_crit && crit.is_some_and(|crit| value >= crit)
+    }
+
+    pub fn is_triggered_by(&self, value: Value, crit: Option<Value>) -> bool {
+        value >= self.trigger_temp.into() || self.reach_crit(value, crit)
+    }
+
+    pub fn is_released_by(&self, value: Value, crit: Option<Value>) -> bool {
+        value < self.release_temp.into() && !self.reach_crit(value, crit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::init_test_logging;
+
+    use super::Emergency;
+
+    #[test]
+    fn test_thresholds() {
+        init_test_logging();
+
+        let emergency = Emergency {
+            trigger_temp: 90,
+            release_temp: 80,
+            use_crit: true,
+            hardware_ids: Vec::new(),
+        };
+
+        assert!(!emergency.is_triggered_by(85, None));
+        assert!(emergency.is_triggered_by(90, None));
+        assert!(emergency.is_triggered_by(75, Some(75)));
+
+        assert!(!emergency.is_released_by(85, None));
+        assert!(emergency.is_released_by(79, None));
+        assert!(!emergency.is_released_by(75, Some(70)));
+
+        let emergency = Emergency {
+            use_crit: false,
+            ..emergency
+        };
+        assert!(!emergency.is_triggered_by(75, Some(75)));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        init_test_logging();
+
+        let emergency = Emergency {
+            trigger_temp: 80,
+            release_temp: 90,
+            ..Default::default()
+        }
+        .sanitize();
+
+        assert!(emergency.release_temp == 79);
+        assert!(emergency.is_triggered_by(80, None));
+        assert!(!emergency.is_released_by(80, None));
+        assert!(emergency.is_released_by(78, None));
+
+        let emergency = Emergency {
+            trigger_temp: 0,
+            release_temp: 0,
+            ..Default::default()
+        }
+        .sanitize();
+        assert!(emergency.release_temp == 0);
+
+        let emergency = Emergency::default().sanitize();
+        assert!(emergency.release_temp == 80);
+    }
+}
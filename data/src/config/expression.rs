@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use hardware::{Hardware, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{Input, IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+use super::utils::expr::{self, Ast, ExprError};
+
+/// Evaluate an expression over named inputs, see the `expr` module for the syntax.
+/// The inputs are the variables used in the expression.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Expression {
+    pub name: String,
+    pub expression: String,
+
+    #[serde(skip)]
+    pub parsed: Option<Result<Ast, ExprError>>,
+}
+
+impl Expression {
+    pub fn new(name: String, expression: String) -> Self {
+        let mut res = Self {
+            name,
+            expression,
+            parsed: None,
+        };
+        res.parse();
+        res
+    }
+
+    pub fn set_expression(&mut self, expression: String) {
+        self.expression = expression;
+        self.parse();
+    }
+
+    fn parse(&mut self) {
+        let parsed = expr::parse(&self.expression);
+
+        if let Err(e) = &parsed {
+            warn!("Can't parse expression of {}: {}.", self.name, e);
+        }
+
+        self.parsed = Some(parsed);
+    }
+
+    pub fn error(&self) -> Option<&ExprError> {
+        match &self.parsed {
+            Some(Err(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn get_inputs(&self) -> Vec<String> {
+        match &self.parsed {
+            Some(Ok(ast)) => ast.variables(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The expression is the only source of truth, so inputs which
+    /// were not found are kept in it. Only renames are applied.
+    pub fn set_inputs(&mut self, inputs: Vec<Option<String>>) {
+        let previous_inputs = self.get_inputs();
+
+        if previous_inputs.len() != inputs.len() {
+            return;
+        }
+
+        let mut expression = self.expression.clone();
+        for (previous, new) in previous_inputs.iter().zip(&inputs) {
+            let Some(new) = new else {
+                continue;
+            };
+
+            if previous == new {
+                continue;
+            }
+
+            match expr::rename_variable(&expression, previous, new) {
+                Ok(renamed) => expression = renamed,
+                Err(e) => {
+                    error!(
+                        "Can't rename {} in expression of {}: {}.",
+                        previous, self.name, e
+                    );
+                    return;
+                }
+            }
+        }
+
+        if expression != self.expression {
+            self.set_expression(expression);
+        }
+    }
+
+    pub fn get_value(&self, inputs: &[Input], values: &[Value]) -> Result<Value, UpdateError> {
+        let Some(Ok(ast)) = &self.parsed else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+
+        let variables: HashMap<&str, f32> = inputs
+            .iter()
+            .zip(values)
+            .map(|(input, value)| (input.name.as_str(), *value as f32))
+            .collect();
+
+        Ok(ast.eval(&variables)?.round() as Value)
+    }
+}
+
+impl IsValid for Expression {
+    fn is_valid(&self) -> bool {
+        matches!(self.parsed, Some(Ok(_)))
+    }
+}
+
+impl ToNode for Expression {
+    fn to_node(
+        mut self,
+        id_generator: &mut IdGenerator,
+        nodes: &Nodes,
+        _hardware: &Hardware,
+    ) -> Node {
+        if self.parsed.is_none() {
+            self.parse();
+        }
+        Node::new(id_generator, NodeType::Expression(self), nodes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{node::Input, utils::init_test_logging};
+
+    use super::Expression;
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let mut expression = Expression::new("expr".into(), "max(cpu, gpu - 10) * 1.2".into());
+
+        assert!(expression.get_inputs() == vec!["cpu".to_string(), "gpu".to_string()]);
+
+        let inputs = [
+            Input {
+                id: 1,
+                name: "cpu".into(),
+            },
+            Input {
+                id: 2,
+                name: "gpu".into(),
+            },
+        ];
+        assert!(expression.get_value(&inputs, &[50, 70]).unwrap() == 72);
+
+        expression.set_inputs(vec![Some("cpu".into()), Some("gpu temp".into())]);
+        assert!(expression.expression == "max(cpu, \"gpu temp\" - 10) * 1.2");
+
+        // a missing input is not removed from the expression
+        expression.set_inputs(vec![Some("cpu".into()), None]);
+        assert!(expression.get_inputs().len() == 2);
+
+        expression.set_expression("max(cpu,".into());
+        assert!(expression.error().is_some());
+    }
+}
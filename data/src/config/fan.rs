@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
 
 use crate::{
     app_graph::Nodes,
@@ -6,6 +6,8 @@ use crate::{
     node::{IsValid, Node, NodeType, ToNode},
     update::UpdateError,
 };
+
+use super::utils::spike_filter::{SpikeFilter, SpikeFilterState};
 use hardware::{HSensor, Hardware, HardwareBridge, Value};
 use serde::{Deserialize, Serialize};
 
@@ -15,16 +17,25 @@ pub struct Fan {
     #[serde(rename = "id")]
     pub hardware_id: Option<String>,
 
+    #[serde(flatten)]
+    pub filter: SpikeFilter,
+
     #[serde(skip)]
     pub fan_h: Option<Rc<HSensor>>,
 }
 
 impl Fan {
-    pub fn get_value<H: HardwareBridge>(&self, bridge: &mut H) -> Result<Value, UpdateError> {
+    pub fn get_value<H: HardwareBridge>(
+        &self,
+        state: &mut SpikeFilterState,
+        bridge: &mut H,
+        now: Instant,
+    ) -> Result<Value, UpdateError> {
         match &self.fan_h {
-            Some(fan_h) => bridge
-                .get_sensor_value(fan_h)
-                .map_err(UpdateError::Hardware),
+            Some(fan_h) => {
+                let value = bridge.get_sensor_value(fan_h)?;
+                self.filter.filter(state, value, now)
+            }
             None => Err(UpdateError::NodeIsInvalid(self.name.clone())),
         }
     }
@@ -32,7 +43,7 @@ impl Fan {
 
 impl IsValid for Fan {
     fn is_valid(&self) -> bool {
-        self.hardware_id.is_some() && self.fan_h.is_some()
+        self.hardware_id.is_some() && self.fan_h.is_some() && self.filter.is_valid()
     }
 }
 
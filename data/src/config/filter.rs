@@ -0,0 +1,169 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use hardware::{Hardware, Value};
+use light_enum::Values;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Smooth one temp over time.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Filter {
+    pub name: String,
+    pub kind: FilterKind,
+    /// Time span in seconds
+    pub span: u16,
+    pub input: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FilterState {
+    pub samples: VecDeque<(Instant, Value)>,
+    pub ema: Option<(f32, Instant)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Values, Default, PartialEq, Eq)]
+pub enum FilterKind {
+    #[default]
+    MovingAverage,
+    Ema,
+    Max,
+}
+
+impl Filter {
+    pub fn get_value(
+        &self,
+        state: &mut FilterState,
+        value: Value,
+        now: Instant,
+    ) -> Result<Value, UpdateError> {
+        let span = Duration::from_secs(self.span.into());
+
+        let value = match self.kind {
+            FilterKind::MovingAverage | FilterKind::Max => {
+                state.samples.push_back((now, value));
+                while state
+                    .samples
+                    .front()
+                    .is_some_and(|(time, _)| now.duration_since(*time) > span)
+                {
+                    state.samples.pop_front();
+                }
+
+                let values = state.samples.iter().map(|(_, value)| *value);
+
+                if self.kind == FilterKind::Max {
+                    values.max().ok_or(UpdateError::NoInputData)?
+                } else {
+                    values.sum::<Value>() / state.samples.len() as Value
+                }
+            }
+            FilterKind::Ema => {
+                let ema = match state.ema {
+                    Some((previous, previous_time)) => {
+                        let dt = now.duration_since(previous_time).as_secs_f32();
+                        let alpha = 1.0 - (-dt / span.as_secs_f32()).exp();
+                        previous + alpha * (value as f32 - previous)
+                    }
+                    None => value as f32,
+                };
+                state.ema = Some((ema, now));
+                ema.round() as Value
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl IsValid for Filter {
+    fn is_valid(&self) -> bool {
+        self.input.is_some() && self.span > 0
+    }
+}
+
+impl ToNode for Filter {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Filter(self), nodes)
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            kind: Default::default(),
+            span: 10,
+            input: Default::default(),
+        }
+    }
+}
+
+impl ToString for FilterKind {
+    fn to_string(&self) -> String {
+        match self {
+            FilterKind::MovingAverage => fl!("moving_average"),
+            FilterKind::Ema => fl!("ema"),
+            FilterKind::Max => fl!("max"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::utils::init_test_logging;
+
+    use super::{Filter, FilterKind, FilterState};
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let filter = Filter {
+            name: "filter".into(),
+            kind: FilterKind::MovingAverage,
+            span: 2,
+            input: Some("temp1".into()),
+        };
+
+        let mut state = FilterState::default();
+
+        assert!(filter.get_value(&mut state, 40, at(0)).unwrap() == 40);
+        assert!(filter.get_value(&mut state, 60, at(1)).unwrap() == 50);
+        assert!(filter.get_value(&mut state, 80, at(2)).unwrap() == 60);
+        assert!(filter.get_value(&mut state, 80, at(3)).unwrap() == 73);
+
+        let filter = Filter {
+            kind: FilterKind::Max,
+            ..filter
+        };
+        let mut state = FilterState::default();
+
+        assert!(filter.get_value(&mut state, 80, at(0)).unwrap() == 80);
+        assert!(filter.get_value(&mut state, 40, at(2)).unwrap() == 80);
+        assert!(filter.get_value(&mut state, 40, at(3)).unwrap() == 40);
+
+        let filter = Filter {
+            kind: FilterKind::Ema,
+            ..filter
+        };
+        let mut state = FilterState::default();
+
+        assert!(filter.get_value(&mut state, 40, at(0)).unwrap() == 40);
+        // after one time constant, 63% of the step is reached
+        assert!(filter.get_value(&mut state, 140, at(2)).unwrap() == 103);
+    }
+}
@@ -1,6 +1,7 @@
 use std::vec;
 
 use hardware::{Hardware, Value};
+use light_enum::Values;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
@@ -10,11 +11,19 @@ use crate::{
     update::UpdateError,
 };
 
-use super::utils::affine::Affine;
+use super::utils::{
+    affine::Affine,
+    hysteresis::{Hysteresis, HysteresisState},
+    spline,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Eq)]
+/// Integers of previous configs are still accepted for `temp`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Coord {
-    pub temp: u8,
+    /// Can be negative or fractional, for inputs like a delta
+    pub temp: f32,
+    /// Output, always between 0 and 100. There is no RPM axis: a control
+    /// driven by RPM reads it as a percentage of `RpmTarget::max_rpm`.
     pub percent: u8,
 }
 
@@ -22,7 +31,11 @@ impl TryFrom<(&str, &str)> for Coord {
     type Error = Box<dyn std::error::Error>;
 
     fn try_from((temp, percent): (&str, &str)) -> Result<Self, Self::Error> {
-        let temp = temp.parse::<u8>()?;
+        let temp = temp.parse::<f32>()?;
+
+        if !temp.is_finite() {
+            return Err("Temp is not finite".into());
+        }
 
         let percent = percent.parse::<u8>()?;
 
@@ -40,6 +53,8 @@ impl PartialEq for Coord {
     }
 }
 
+impl Eq for Coord {}
+
 impl PartialOrd for Coord {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -48,7 +63,7 @@ impl PartialOrd for Coord {
 
 impl Ord for Coord {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.temp.cmp(&other.temp)
+        self.temp.total_cmp(&other.temp)
     }
 }
 
@@ -64,6 +79,31 @@ pub struct Graph {
     #[serde(rename = "coord")]
     pub coords: Coords,
     pub input: Option<String>, // Temp or CustomTemp
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    #[serde(flatten)]
+    pub hysteresis: Hysteresis,
+}
+
+/// How the percent is computed between two coords.
+#[derive(Serialize, Deserialize, Debug, Clone, Values, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold the percent of the previous coord
+    Step,
+    #[default]
+    Linear,
+    /// Monotone cubic spline
+    Spline,
+}
+
+impl ToString for Interpolation {
+    fn to_string(&self) -> String {
+        match self {
+            Interpolation::Step => fl!("step"),
+            Interpolation::Linear => fl!("linear"),
+            Interpolation::Spline => fl!("spline"),
+        }
+    }
 }
 
 impl Default for Graph {
@@ -72,15 +112,17 @@ impl Default for Graph {
             name: Default::default(),
             coords: Coords(vec![
                 Coord {
-                    temp: 10,
+                    temp: 10.0,
                     percent: 10,
                 },
                 Coord {
-                    temp: 70,
+                    temp: 70.0,
                     percent: 100,
                 },
             ]),
             input: Default::default(),
+            interpolation: Default::default(),
+            hysteresis: Default::default(),
         }
     }
 }
@@ -92,61 +134,69 @@ impl ToNode for Graph {
 }
 
 impl IsValid for Graph {
-    // todo: we can filter wrong Coord when launching
-    // and only autorize good coord to be added so we don't need this
-    // kind on verification
-    // so only check if !empty
+    // coords are validated when they are loaded or added
     fn is_valid(&self) -> bool {
-        #[derive(PartialEq)]
-        enum DupState {
-            Init,
-            Prev { temp: u8 },
-            DuplicateFound,
-        }
-
-        self.input.is_some()
-            && !self.coords.0.is_empty()
-            && self
-                .coords
-                .0
-                .iter()
-                .fold(DupState::Init, |prev, coord| match prev {
-                    DupState::Init => DupState::Prev { temp: coord.temp },
-                    DupState::Prev { temp } => {
-                        if temp == coord.temp {
-                            DupState::DuplicateFound
-                        } else {
-                            DupState::Prev { temp: coord.temp }
-                        }
-                    }
-                    DupState::DuplicateFound => DupState::DuplicateFound,
-                })
-                != DupState::DuplicateFound
-            && !self.coords.0.iter().any(|coord| coord.percent > 100)
+        self.input.is_some() && !self.coords.0.is_empty()
     }
 }
 
 #[derive(Serialize, Debug, Clone, Default)]
 pub struct Coords(pub Vec<Coord>);
 
+impl TryFrom<Vec<Coord>> for Coords {
+    type Error = String;
+
+    /// Sort the coords, and reject the ones that can't be interpolated.
+    fn try_from(mut coords: Vec<Coord>) -> Result<Self, Self::Error> {
+        if let Some(coord) = coords.iter().find(|c| !c.temp.is_finite()) {
+            return Err(format!("the temp of a coord is not finite: {}", coord.temp));
+        }
+
+        if let Some(coord) = coords.iter().find(|c| c.percent > 100) {
+            return Err(format!("percent > 100 at temp {}", coord.temp));
+        }
+
+        coords.sort();
+
+        if let Some(w) = coords.windows(2).find(|w| w[0].temp == w[1].temp) {
+            return Err(format!("duplicate coord at temp {}", w[0].temp));
+        }
+
+        Ok(Coords(coords))
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for Coords {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        let mut s: Vec<Coord> = Vec::deserialize(d)?;
+        let coords: Vec<Coord> = Vec::deserialize(d)?;
 
-        s.sort();
-
-        Ok(Coords(s))
+        Coords::try_from(coords).map_err(serde::de::Error::custom)
     }
 }
 
 impl Graph {
-    pub fn get_value(&self, value: Value) -> Result<Value, UpdateError> {
+    pub fn get_value(
+        &self,
+        state: &mut HysteresisState,
+        value: Value,
+    ) -> Result<Value, UpdateError> {
+        let temp = self.hysteresis.temp(state, value);
+        let res = self.interpolate(temp);
+
+        let percents = self.coords.0.iter().map(|coord| Value::from(coord.percent));
+        let min = percents.clone().min().unwrap_or(res);
+        let max = percents.max().unwrap_or(res);
+
+        Ok(self.hysteresis.output(state, res, min, max))
+    }
+
+    fn interpolate(&self, value: Value) -> Value {
         let dummy_coord = Coord {
-            temp: value as u8,
+            temp: value as f32,
             percent: 0,
         };
 
-        let res = match self.coords.0.binary_search(&dummy_coord) {
+        match self.coords.0.binary_search(&dummy_coord) {
             Ok(index) => self.coords.0[index].percent as Value,
             Err(index) => {
                 if index == 0 {
@@ -157,70 +207,135 @@ impl Graph {
                     let coord1 = &self.coords.0[index - 1];
                     let coord2 = &self.coords.0[index];
 
-                    Affine {
-                        xa: coord1.temp.into(),
-                        ya: coord1.percent.into(),
-                        xb: coord2.temp.into(),
-                        yb: coord2.percent.into(),
+                    match self.interpolation {
+                        Interpolation::Step => coord1.percent as Value,
+                        Interpolation::Linear => Affine {
+                            xa: coord1.temp,
+                            ya: coord1.percent.into(),
+                            xb: coord2.temp,
+                            yb: coord2.percent.into(),
+                        }
+                        .calcule(value) as Value,
+                        Interpolation::Spline => {
+                            let points: Vec<(f32, f32)> = self
+                                .coords
+                                .0
+                                .iter()
+                                .map(|coord| (coord.temp, coord.percent.into()))
+                                .collect();
+
+                            spline::monotone_cubic(&points, value as f32).round() as Value
+                        }
                     }
-                    .calcule(value) as Value
                 }
             }
-        };
-
-        Ok(res)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::config::graph::{Coord, Coords};
+    use crate::config::graph::{Coord, Coords, Graph, Interpolation};
 
     #[test]
-    fn test() {
-        let coord1 = Coord {
-            temp: 10,
-            percent: 10,
-        };
-
-        let coord2 = Coord {
-            temp: 20,
-            percent: 20,
-        };
-
-        let coord3 = Coord {
-            temp: 30,
-            percent: 30,
+    fn test_interpolation() {
+        let mut graph = Graph {
+            coords: Coords(vec![
+                Coord {
+                    temp: 20.0,
+                    percent: 20,
+                },
+                Coord {
+                    temp: 40.0,
+                    percent: 30,
+                },
+                Coord {
+                    temp: 60.0,
+                    percent: 80,
+                },
+            ]),
+            ..Default::default()
         };
 
-        let coord4 = Coord {
-            temp: 40,
-            percent: 40,
-        };
+        assert!(graph.interpolate(30) == 25);
+        assert!(graph.interpolate(50) == 55);
 
-        let coords = Coords(vec![coord1, coord2, coord3, coord4]);
+        graph.interpolation = Interpolation::Step;
+        assert!(graph.interpolate(30) == 20);
+        assert!(graph.interpolate(59) == 30);
+        assert!(graph.interpolate(60) == 80);
 
-        let dummy_coord = Coord {
-            temp: 50,
-            percent: 0,
-        };
+        graph.interpolation = Interpolation::Spline;
+        let spline = graph.interpolate(50);
+        assert!(spline > 30 && spline < 80 && spline != 55);
+        assert!(graph.interpolate(70) == 80);
+    }
 
-        let res = coords.0.binary_search(&dummy_coord);
+    #[test]
+    fn test_coords() {
+        // integers of previous configs
+        let graph: Graph = toml::from_str(
+            r#"
+            name = "graph"
+            coord = [
+                { temp = 50, percent = 30 },
+                { temp = 10, percent = 10 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(graph.coords.0[0].temp == 10.0);
+
+        let graph: Graph = toml::from_str(
+            r#"
+            name = "graph"
+            coord = [
+                { temp = -10, percent = 0 },
+                { temp = 2.5, percent = 40 },
+                { temp = 300, percent = 100 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(graph.interpolate(-20) == 0);
+        assert!(graph.interpolate(1000) == 100);
+
+        let duplicate = toml::from_str::<Graph>(
+            r#"
+            name = "graph"
+            coord = [
+                { temp = 10, percent = 10 },
+                { temp = 10.0, percent = 20 },
+            ]
+            "#,
+        );
+        assert!(duplicate.is_err());
+
+        let percent = toml::from_str::<Graph>(
+            r#"
+            name = "graph"
+            coord = [{ temp = 10, percent = 101 }]
+            "#,
+        );
+        assert!(percent.is_err());
+    }
 
-        match res {
-            Ok(index) => {
-                println!("use {}", index);
-            }
-            Err(index) => {
-                if index == 0 {
-                    println!("use {}", index);
-                } else if index == coords.0.len() {
-                    println!("use {}", index - 1);
-                } else {
-                    println!("use {} and {}", index - 1, index);
-                }
-            }
-        }
-        dbg!(&res);
+    #[test]
+    fn test_search() {
+        let coord = |temp: f32, percent| Coord { temp, percent };
+        let coords = Coords(vec![
+            coord(10.0, 10),
+            coord(20.0, 20),
+            coord(30.0, 30),
+            coord(40.0, 40),
+        ]);
+
+        // the percent is not part of the order
+        assert!(coords.0.binary_search(&coord(20.0, 0)) == Ok(1));
+        assert!(coords.0.binary_search(&coord(5.0, 0)) == Err(0));
+        assert!(coords.0.binary_search(&coord(25.0, 0)) == Err(2));
+        assert!(coords.0.binary_search(&coord(50.0, 0)) == Err(4));
     }
 }
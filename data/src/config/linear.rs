@@ -7,7 +7,10 @@ use crate::{
 use hardware::{Hardware, Value};
 use serde::{Deserialize, Serialize};
 
-use super::utils::affine::Affine;
+use super::utils::{
+    affine::Affine,
+    hysteresis::{Hysteresis, HysteresisState},
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Linear {
@@ -21,6 +24,8 @@ pub struct Linear {
     #[serde(rename = "maxSpeed", alias = "max_speed")]
     pub max_speed: u8,
     pub input: Option<String>,
+    #[serde(flatten)]
+    pub hysteresis: Hysteresis,
 }
 
 impl IsValid for Linear {
@@ -30,24 +35,35 @@ impl IsValid for Linear {
 }
 
 impl Linear {
-    pub fn get_value(&self, value: Value) -> Result<Value, UpdateError> {
+    pub fn get_value(
+        &self,
+        state: &mut HysteresisState,
+        value: Value,
+    ) -> Result<Value, UpdateError> {
+        let temp = self.hysteresis.temp(state, value);
+        let res = self.interpolate(temp);
+
+        Ok(self
+            .hysteresis
+            .output(state, res, self.min_speed.into(), self.max_speed.into()))
+    }
+
+    fn interpolate(&self, value: Value) -> Value {
         if value <= self.min_temp.into() {
-            return Ok(self.min_speed.into());
+            return self.min_speed.into();
         }
 
         if value >= self.max_temp.into() {
-            return Ok(self.max_speed.into());
+            return self.max_speed.into();
         }
 
-        let res = Affine {
+        Affine {
             xa: self.min_temp.into(),
             ya: self.min_speed.into(),
             xb: self.max_temp.into(),
             yb: self.max_speed.into(),
         }
-        .calcule(value) as Value;
-
-        Ok(res)
+        .calcule(value) as Value
     }
 }
 
@@ -66,6 +82,7 @@ impl Default for Linear {
             max_temp: 70,
             max_speed: 100,
             input: Default::default(),
+            hysteresis: Default::default(),
         }
     }
 }
@@ -74,6 +91,8 @@ impl Default for Linear {
 mod test {
     use crate::utils::init_test_logging;
 
+    use crate::config::utils::hysteresis::HysteresisState;
+
     use super::Linear;
 
     #[test]
@@ -87,10 +106,12 @@ mod test {
             max_temp: 70,
             max_speed: 100,
             input: Some("temp1".into()),
+            hysteresis: Default::default(),
         };
+        let mut state = HysteresisState::default();
 
-        assert!(linear.get_value(9).unwrap() == 10);
-        assert!(linear.get_value(70).unwrap() == 100);
-        assert!(linear.get_value(40).unwrap() == 55);
+        assert!(linear.get_value(&mut state, 9).unwrap() == 10);
+        assert!(linear.get_value(&mut state, 70).unwrap() == 100);
+        assert!(linear.get_value(&mut state, 40).unwrap() == 55);
     }
 }
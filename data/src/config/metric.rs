@@ -0,0 +1,74 @@
+use std::rc::Rc;
+
+use hardware::{HSensor, Hardware, HardwareBridge, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Utilization of the system (CPU usage, load, pressure), in %.
+/// It rise before the temps, so behaviors can anticipate them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Metric {
+    pub name: String,
+    #[serde(rename = "id")]
+    pub hardware_id: Option<String>,
+
+    #[serde(skip)]
+    pub metric_h: Option<Rc<HSensor>>,
+}
+
+impl Metric {
+    pub fn get_value<H: HardwareBridge>(&self, bridge: &mut H) -> Result<Value, UpdateError> {
+        match &self.metric_h {
+            Some(metric_h) => bridge
+                .get_metric_value(metric_h)
+                .map_err(UpdateError::Hardware),
+            None => Err(UpdateError::NodeIsInvalid(self.name.clone())),
+        }
+    }
+}
+
+impl IsValid for Metric {
+    fn is_valid(&self) -> bool {
+        self.hardware_id.is_some() && self.metric_h.is_some()
+    }
+}
+
+impl ToNode for Metric {
+    fn to_node(
+        mut self,
+        id_generator: &mut IdGenerator,
+        nodes: &Nodes,
+        hardware: &Hardware,
+    ) -> Node {
+        match &self.hardware_id {
+            Some(hardware_id) => {
+                match hardware
+                    .metrics
+                    .iter()
+                    .find(|metric_h| &metric_h.hardware_id == hardware_id)
+                {
+                    Some(metric_h) => self.metric_h = Some(metric_h.clone()),
+                    None => {
+                        warn!("Metric to Node, hardware_id not found. {} from config not found. Fall back to no id", hardware_id);
+                        self.hardware_id.take();
+                        self.metric_h.take();
+                    }
+                }
+            }
+            None => {
+                if self.metric_h.is_some() {
+                    warn!("Metric to Node: inconsistent internal index");
+                    self.metric_h.take();
+                }
+            }
+        }
+
+        Node::new(id_generator, NodeType::Metric(self), nodes)
+    }
+}
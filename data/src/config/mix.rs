@@ -0,0 +1,96 @@
+use hardware::{Hardware, Value};
+use light_enum::Values;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Combine the values of several behaviors.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Mix {
+    pub name: String,
+    pub kind: MixKind,
+    pub inputs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Values, Default, PartialEq, Eq)]
+pub enum MixKind {
+    #[default]
+    Max,
+    Min,
+    Average,
+    Sum,
+}
+
+impl Mix {
+    pub fn get_value(&self, values: &[Value]) -> Result<Value, UpdateError> {
+        if values.is_empty() {
+            return Err(UpdateError::NoInputData);
+        }
+
+        let value = match self.kind {
+            MixKind::Max => *values.iter().max().unwrap(),
+            MixKind::Min => *values.iter().min().unwrap(),
+            MixKind::Average => values.iter().sum::<Value>() / values.len() as Value,
+            // the result is a percentage
+            MixKind::Sum => values.iter().sum::<Value>().min(100),
+        };
+
+        Ok(value)
+    }
+}
+
+impl IsValid for Mix {
+    fn is_valid(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+}
+
+impl ToNode for Mix {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Mix(self), nodes)
+    }
+}
+
+impl ToString for MixKind {
+    fn to_string(&self) -> String {
+        match self {
+            MixKind::Max => fl!("max"),
+            MixKind::Min => fl!("min"),
+            MixKind::Average => fl!("average"),
+            MixKind::Sum => fl!("sum"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::init_test_logging;
+
+    use super::{Mix, MixKind};
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let mut mix = Mix {
+            name: "mix".into(),
+            kind: MixKind::Max,
+            inputs: vec!["cpu curve".into(), "gpu curve".into()],
+        };
+
+        assert!(mix.get_value(&[30, 60]).unwrap() == 60);
+        mix.kind = MixKind::Min;
+        assert!(mix.get_value(&[30, 60]).unwrap() == 30);
+        mix.kind = MixKind::Average;
+        assert!(mix.get_value(&[30, 60]).unwrap() == 45);
+        mix.kind = MixKind::Sum;
+        assert!(mix.get_value(&[30, 60]).unwrap() == 90);
+        assert!(mix.get_value(&[50, 60]).unwrap() == 100);
+        assert!(mix.get_value(&[]).is_err());
+    }
+}
@@ -1,13 +1,22 @@
 pub mod control;
 pub mod custom_temp;
+pub mod delta;
+pub mod emergency;
+pub mod expression;
 pub mod fan;
+pub mod filter;
 pub mod flat;
 pub mod graph;
 pub mod linear;
+pub mod metric;
+pub mod mix;
+pub mod pid;
+pub mod schedule;
+pub mod switch;
 pub mod target;
 pub mod temp;
 
-mod utils;
+pub mod utils;
 
 #[cfg(test)]
 mod serde_test;
@@ -15,8 +24,10 @@ mod serde_test;
 use crate::{
     app_graph::AppGraph,
     config::{
-        control::Control, custom_temp::CustomTemp, fan::Fan, flat::Flat, graph::Graph,
-        linear::Linear, target::Target, temp::Temp,
+        control::Control, custom_temp::CustomTemp, delta::Delta, emergency::Emergency,
+        expression::Expression, fan::Fan, filter::Filter, flat::Flat, graph::Graph, linear::Linear,
+        metric::Metric, mix::Mix, pid::Pid, schedule::Schedule, switch::Switch, target::Target,
+        temp::Temp,
     },
     node::{self},
 };
@@ -31,8 +42,16 @@ pub struct Config {
     pub fans: Vec<Fan>,
     #[serde(default, rename = "Temp")]
     pub temps: Vec<Temp>,
+    #[serde(default, rename = "Metric")]
+    pub metrics: Vec<Metric>,
     #[serde(default, rename = "CustomTemp")]
     pub custom_temps: Vec<CustomTemp>,
+    #[serde(default, rename = "Filter")]
+    pub filters: Vec<Filter>,
+    #[serde(default, rename = "Delta")]
+    pub deltas: Vec<Delta>,
+    #[serde(default, rename = "Expression")]
+    pub expressions: Vec<Expression>,
     #[serde(default, rename = "Graph")]
     pub graphs: Vec<Graph>,
     #[serde(default, rename = "Flat")]
@@ -41,16 +60,30 @@ pub struct Config {
     pub linears: Vec<Linear>,
     #[serde(default, rename = "Target")]
     pub targets: Vec<Target>,
+    #[serde(default, rename = "Pid")]
+    pub pids: Vec<Pid>,
+    #[serde(default, rename = "Mix")]
+    pub mixs: Vec<Mix>,
+    #[serde(default, rename = "Switch")]
+    pub switches: Vec<Switch>,
+    #[serde(default, rename = "Schedule")]
+    pub schedules: Vec<Schedule>,
+    #[serde(default, rename = "Emergency")]
+    pub emergency: Option<Emergency>,
 }
 
 impl Config {
     pub fn from_app_graph(app_graph: &AppGraph) -> Self {
-        let mut config = Config::default();
+        let mut config = Config {
+            emergency: app_graph.emergency.clone(),
+            ..Default::default()
+        };
         for node in app_graph.nodes.values() {
             match &node.node_type {
                 node::NodeType::Control(control) => config.controls.push(control.clone()),
                 node::NodeType::Fan(fan) => config.fans.push(fan.clone()),
                 node::NodeType::Temp(temp) => config.temps.push(temp.clone()),
+                node::NodeType::Metric(metric) => config.metrics.push(metric.clone()),
                 node::NodeType::CustomTemp(custom_temp) => {
                     config.custom_temps.push(custom_temp.clone())
                 }
@@ -58,6 +91,15 @@ impl Config {
                 node::NodeType::Flat(flat) => config.flats.push(flat.clone()),
                 node::NodeType::Linear(linear, ..) => config.linears.push(linear.clone()),
                 node::NodeType::Target(target, ..) => config.targets.push(target.clone()),
+                node::NodeType::Pid(pid) => config.pids.push(pid.clone()),
+                node::NodeType::Filter(filter) => config.filters.push(filter.clone()),
+                node::NodeType::Delta(delta) => config.deltas.push(delta.clone()),
+                node::NodeType::Mix(mix) => config.mixs.push(mix.clone()),
+                node::NodeType::Switch(switch) => config.switches.push(switch.clone()),
+                node::NodeType::Schedule(schedule) => config.schedules.push(schedule.clone()),
+                node::NodeType::Expression(expression) => {
+                    config.expressions.push(expression.clone())
+                }
             }
         }
         config
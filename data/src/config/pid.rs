@@ -0,0 +1,163 @@
+use std::time::Instant;
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+use hardware::{Hardware, Value};
+use serde::{Deserialize, Serialize};
+
+/// Closed-loop controller which try to hold the input at the setpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pid {
+    pub name: String,
+    pub setpoint: u8,
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    #[serde(rename = "minSpeed", alias = "min_speed")]
+    pub min_speed: u8,
+    #[serde(rename = "maxSpeed", alias = "max_speed")]
+    pub max_speed: u8,
+    /// Stop integrating while the output is saturated
+    #[serde(
+        default = "default_anti_windup",
+        rename = "antiWindup",
+        alias = "anti_windup"
+    )]
+    pub anti_windup: bool,
+    pub input: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PidState {
+    pub integral: f32,
+    /// Last error and when it was computed
+    pub previous: Option<(f32, Instant)>,
+}
+
+fn default_anti_windup() -> bool {
+    true
+}
+
+impl Pid {
+    pub fn get_value(
+        &self,
+        state: &mut PidState,
+        value: Value,
+        now: Instant,
+    ) -> Result<Value, UpdateError> {
+        // positive when it's too hot, so the output increase
+        let error = (value - Value::from(self.setpoint)) as f32;
+
+        let (integral, derivative) = match state.previous {
+            Some((previous_error, previous_time)) => {
+                let dt = now.duration_since(previous_time).as_secs_f32();
+                let derivative = if dt > 0.0 {
+                    (error - previous_error) / dt
+                } else {
+                    0.0
+                };
+                (state.integral + error * dt, derivative)
+            }
+            None => (state.integral, 0.0),
+        };
+
+        let min = f32::from(self.min_speed);
+        let max = f32::from(self.max_speed);
+
+        let output = self.kp * error + self.ki * integral + self.kd * derivative;
+
+        let winding_up = (output > max && error > 0.0) || (output < min && error < 0.0);
+        if !(self.anti_windup && winding_up) {
+            state.integral = integral;
+        }
+        state.previous = Some((error, now));
+
+        Ok(output.clamp(min, max).round() as Value)
+    }
+}
+
+impl IsValid for Pid {
+    fn is_valid(&self) -> bool {
+        self.input.is_some()
+            && self.max_speed > self.min_speed
+            && self.kp.is_finite()
+            && self.ki.is_finite()
+            && self.kd.is_finite()
+    }
+}
+
+impl ToNode for Pid {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Pid(self), nodes)
+    }
+}
+
+impl Default for Pid {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            setpoint: 50,
+            kp: 5.0,
+            ki: 0.1,
+            kd: 0.0,
+            min_speed: 0,
+            max_speed: 100,
+            anti_windup: default_anti_windup(),
+            input: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::utils::init_test_logging;
+
+    use super::{Pid, PidState};
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let pid = Pid {
+            name: "pid".to_string(),
+            setpoint: 50,
+            kp: 2.0,
+            ki: 0.5,
+            kd: 0.0,
+            min_speed: 0,
+            max_speed: 100,
+            anti_windup: true,
+            input: Some("temp1".into()),
+        };
+        let mut state = PidState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(pid.get_value(&mut state, 60, at(0)).unwrap() == 20);
+        assert!(pid.get_value(&mut state, 60, at(2)).unwrap() == 30);
+        assert!(pid.get_value(&mut state, 50, at(4)).unwrap() == 10);
+
+        // saturated: the integral is frozen
+        assert!(pid.get_value(&mut state, 150, at(5)).unwrap() == 100);
+        assert!(pid.get_value(&mut state, 50, at(6)).unwrap() == 10);
+
+        let pid = Pid {
+            anti_windup: false,
+            ..pid
+        };
+        let mut state = PidState {
+            integral: 20.0,
+            previous: None,
+        };
+        pid.get_value(&mut state, 50, at(0)).unwrap();
+        assert!(pid.get_value(&mut state, 150, at(1)).unwrap() == 100);
+        assert!(pid.get_value(&mut state, 50, at(2)).unwrap() == 60);
+    }
+}
@@ -0,0 +1,395 @@
+use std::fmt::Display;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use hardware::{Hardware, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{Input, IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Select a behavior depending on the local time and weekday.
+/// The global emergency still apply, whatever the period, and the caps
+/// of the periods are lifted while `override_condition` is at or above
+/// `override_threshold`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Schedule {
+    pub name: String,
+    /// Behavior used outside of the periods
+    pub input: Option<String>,
+    #[serde(default)]
+    pub periods: Vec<Period>,
+    /// Sensor compared to the override threshold
+    #[serde(
+        default,
+        rename = "overrideCondition",
+        alias = "override_condition",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub override_condition: Option<String>,
+    #[serde(default, rename = "overrideThreshold", alias = "override_threshold")]
+    pub override_threshold: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Period {
+    pub start: TimeOfDay,
+    /// Can be before `start`, the period then end the next day
+    pub end: TimeOfDay,
+    /// Days on which the period start. Every day if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Day>,
+    /// Behavior used during the period, the default one if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// Cap of the output during the period
+    #[serde(
+        default,
+        rename = "maxSpeed",
+        alias = "max_speed",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_speed: Option<u8>,
+}
+
+/// A time in the format "HH:MM".
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Day {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Schedule {
+    /// Slot of the override condition, the other slots are behaviors.
+    pub const OVERRIDE_SLOT: usize = 1;
+
+    /// Inputs by slot: the default one, the override condition,
+    /// then one for each period.
+    pub fn get_inputs(&self) -> Vec<Option<String>> {
+        let mut inputs = vec![self.input.clone(), self.override_condition.clone()];
+        inputs.extend(self.periods.iter().map(|p| p.input.clone()));
+        inputs
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<Option<String>>) {
+        let mut inputs = inputs.into_iter();
+        self.input = inputs.next().flatten();
+        self.override_condition = inputs.next().flatten();
+        for period in &mut self.periods {
+            period.input = inputs.next().flatten();
+        }
+    }
+
+    /// Return the period active at this time, if any.
+    pub fn current_period(&self, now: NaiveDateTime) -> Option<&Period> {
+        self.periods.iter().find(|p| p.contains(now))
+    }
+
+    pub fn get_value(
+        &self,
+        inputs: &[Input],
+        values: &[Value],
+        now: NaiveDateTime,
+    ) -> Result<Value, UpdateError> {
+        let period = self.current_period(now);
+
+        let Some(name) = period
+            .and_then(|p| p.input.as_ref())
+            .or(self.input.as_ref())
+        else {
+            return Err(UpdateError::NodeIsInvalid(self.name.clone()));
+        };
+
+        let value_of = |name: &String| {
+            inputs
+                .iter()
+                .zip(values)
+                .find(|(input, _)| &input.name == name)
+                .map(|(_, value)| *value)
+                .ok_or(UpdateError::NoInputData)
+        };
+
+        let value = value_of(name)?;
+
+        let Some(max_speed) = period.and_then(|p| p.max_speed) else {
+            return Ok(value);
+        };
+
+        if let Some(condition) = &self.override_condition {
+            if value_of(condition)? >= self.override_threshold.into() {
+                return Ok(value);
+            }
+        }
+
+        Ok(value.min(max_speed.into()))
+    }
+}
+
+impl Period {
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let time = TimeOfDay::from(now.time());
+        let weekday = now.weekday();
+
+        if self.start < self.end {
+            self.is_day(weekday) && self.start <= time && time < self.end
+        } else if self.start > self.end {
+            // the part after midnight belong to the previous day
+            (self.is_day(weekday) && self.start <= time)
+                || (self.is_day(weekday.pred()) && time < self.end)
+        } else {
+            self.is_day(weekday)
+        }
+    }
+
+    fn is_day(&self, weekday: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&weekday.into())
+    }
+}
+
+impl IsValid for Schedule {
+    fn is_valid(&self) -> bool {
+        self.input.is_some()
+    }
+}
+
+impl ToNode for Schedule {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Schedule(self), nodes)
+    }
+}
+
+impl From<NaiveTime> for TimeOfDay {
+    fn from(value: NaiveTime) -> Self {
+        Self {
+            hour: value.hour() as u8,
+            minute: value.minute() as u8,
+        }
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let error = || format!("invalid time {value:?}, expected HH:MM");
+
+        let (hour, minute) = value.split_once(':').ok_or_else(error)?;
+        let hour: u8 = hour.trim().parse().map_err(|_| error())?;
+        let minute: u8 = minute.trim().parse().map_err(|_| error())?;
+
+        if hour > 23 || minute > 59 {
+            return Err(error());
+        }
+
+        Ok(Self { hour, minute })
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(value: TimeOfDay) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl From<Weekday> for Day {
+    fn from(value: Weekday) -> Self {
+        match value {
+            Weekday::Mon => Day::Mon,
+            Weekday::Tue => Day::Tue,
+            Weekday::Wed => Day::Wed,
+            Weekday::Thu => Day::Thu,
+            Weekday::Fri => Day::Fri,
+            Weekday::Sat => Day::Sat,
+            Weekday::Sun => Day::Sun,
+        }
+    }
+}
+
+impl ToString for Day {
+    fn to_string(&self) -> String {
+        match self {
+            Day::Mon => fl!("mon"),
+            Day::Tue => fl!("tue"),
+            Day::Wed => fl!("wed"),
+            Day::Thu => fl!("thu"),
+            Day::Fri => fl!("fri"),
+            Day::Sat => fl!("sat"),
+            Day::Sun => fl!("sun"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use crate::{node::Input, utils::init_test_logging};
+
+    use super::{Day, Period, Schedule, TimeOfDay};
+
+    // 2024-01-01 is a monday
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(s: &str) -> TimeOfDay {
+        TimeOfDay::try_from(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_period() {
+        init_test_logging();
+
+        let night = Period {
+            start: time("22:00"),
+            end: time("07:00"),
+            days: vec![Day::Mon],
+            ..Default::default()
+        };
+
+        assert!(!night.contains(at(1, 21, 59)));
+        assert!(night.contains(at(1, 22, 0)));
+        // tuesday morning is still the night of monday
+        assert!(night.contains(at(2, 6, 59)));
+        assert!(!night.contains(at(2, 7, 0)));
+        assert!(!night.contains(at(2, 22, 0)));
+        assert!(!night.contains(at(1, 3, 0)));
+
+        let meeting = Period {
+            start: time("14:00"),
+            end: time("15:30"),
+            ..Default::default()
+        };
+
+        assert!(meeting.contains(at(3, 15, 29)));
+        assert!(!meeting.contains(at(3, 15, 30)));
+
+        assert!(TimeOfDay::try_from("24:00".to_string()).is_err());
+        assert!(TimeOfDay::try_from("7h".to_string()).is_err());
+        assert!(time("7:05").to_string() == "07:05");
+    }
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let mut schedule = Schedule {
+            name: "schedule".into(),
+            input: Some("normal".into()),
+            periods: vec![
+                Period {
+                    start: time("22:00"),
+                    end: time("07:00"),
+                    input: Some("quiet".into()),
+                    ..Default::default()
+                },
+                Period {
+                    start: time("14:00"),
+                    end: time("15:00"),
+                    max_speed: Some(40),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert!(
+            schedule.get_inputs() == vec![Some("normal".into()), None, Some("quiet".into()), None]
+        );
+
+        let inputs = [
+            Input {
+                id: 1,
+                name: "normal".into(),
+            },
+            Input {
+                id: 2,
+                name: "quiet".into(),
+            },
+        ];
+        let values = [70, 30];
+
+        assert!(schedule.get_value(&inputs, &values, at(1, 12, 0)).unwrap() == 70);
+        assert!(schedule.get_value(&inputs, &values, at(1, 23, 0)).unwrap() == 30);
+        assert!(schedule.get_value(&inputs, &values, at(1, 14, 30)).unwrap() == 40);
+
+        schedule.set_inputs(vec![Some("normal".into()), None, None, None]);
+        assert!(schedule.periods[0].input.is_none());
+        assert!(schedule.get_value(&inputs, &values, at(1, 23, 0)).unwrap() == 70);
+    }
+
+    #[test]
+    fn test_override() {
+        init_test_logging();
+
+        let schedule = Schedule {
+            name: "schedule".into(),
+            input: Some("normal".into()),
+            periods: vec![Period {
+                start: time("14:00"),
+                end: time("15:00"),
+                max_speed: Some(40),
+                ..Default::default()
+            }],
+            override_condition: Some("cpu".into()),
+            override_threshold: 85,
+        };
+
+        let inputs = [
+            Input {
+                id: 1,
+                name: "normal".into(),
+            },
+            Input {
+                id: 2,
+                name: "cpu".into(),
+            },
+        ];
+
+        assert!(schedule.get_inputs()[Schedule::OVERRIDE_SLOT] == Some("cpu".into()));
+
+        assert!(
+            schedule
+                .get_value(&inputs, &[70, 60], at(1, 14, 30))
+                .unwrap()
+                == 40
+        );
+        // the cap is lifted
+        assert!(
+            schedule
+                .get_value(&inputs, &[70, 85], at(1, 14, 30))
+                .unwrap()
+                == 70
+        );
+        assert!(
+            schedule
+                .get_value(&inputs, &[70, 60], at(1, 12, 0))
+                .unwrap()
+                == 70
+        );
+    }
+}
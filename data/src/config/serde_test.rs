@@ -13,15 +13,26 @@ use crate::config::Config;
 
 use crate::settings::Settings;
 
-use super::control::Control;
+use super::control::{Control, Member, RpmTarget, StartBoost};
 use super::custom_temp::{CustomTemp, CustomTempKind};
+use super::delta::Delta;
+use super::emergency::Emergency;
+use super::expression::Expression;
 
 use super::fan::Fan;
+use super::filter::{Filter, FilterKind};
 use super::flat::Flat;
-use super::graph::{Coord, Coords, Graph};
+use super::graph::{Coord, Coords, Graph, Interpolation};
 use super::linear::Linear;
+use super::metric::Metric;
+use super::mix::{Mix, MixKind};
+use super::pid::Pid;
+use super::schedule::{Day, Period, Schedule, TimeOfDay};
+use super::switch::Switch;
 use super::target::Target;
 use super::temp::Temp;
+use super::utils::hysteresis::Hysteresis;
+use super::utils::spike_filter::SpikeFilter;
 
 const SETTINGS_DIR_PATH: &str = "./.test/config/";
 
@@ -130,41 +141,90 @@ fn hardware1() -> Hardware {
 
 fn config1() -> Config {
     Config {
-        controls: vec![Control::new(
-            "Control".into(),
-            Some("Control".into()),
-            None,
-            true,
-            None,
-        )],
+        controls: vec![Control {
+            members: vec![Member {
+                hardware_id: "control2".into(),
+                scale: Some(0.8),
+                offset: Some(10),
+                ..Default::default()
+            }],
+            min: Some(20),
+            max: Some(90),
+            emergency_value: Some(100),
+            stop_below: Some(20),
+            min_duty: Some(30),
+            start_boost: Some(StartBoost::default()),
+            rpm: Some(RpmTarget {
+                fan_id: Some("fan".into()),
+                max_rpm: 2000,
+                gain: 0.5,
+                ..Default::default()
+            }),
+            ..Control::new("Control".into(), Some("Control".into()), None, true, None)
+        }],
         temps: vec![Temp {
             name: "Temp".into(),
             hardware_id: Some("temp".into()),
+            filter: SpikeFilter {
+                min: Some(0),
+                max: Some(110),
+                ..Default::default()
+            },
             temp_h: None,
         }],
         fans: vec![Fan {
             name: "Fan".into(),
             hardware_id: None,
+            filter: SpikeFilter {
+                median: Some(3),
+                ..Default::default()
+            },
             fan_h: None,
         }],
+        metrics: vec![Metric {
+            name: "Metric".into(),
+            hardware_id: Some("system-cpu-usage".into()),
+            metric_h: None,
+        }],
         custom_temps: vec![CustomTemp::new(
             "CustomTemp".into(),
             CustomTempKind::Max,
             vec!["temp1".into(), "temp2".into()],
         )],
+        expressions: vec![Expression::new(
+            "Expression".into(),
+            "max(temp1, temp2 - 10) * 1.2".into(),
+        )],
         graphs: vec![Graph {
             name: "Graph".into(),
             coords: Coords(vec![
                 Coord {
-                    temp: 10,
+                    temp: 10.0,
                     percent: 10,
                 },
                 Coord {
-                    temp: 50,
+                    temp: 50.5,
                     percent: 30,
                 },
             ]),
             input: Some("max".into()),
+            interpolation: Interpolation::Spline,
+            hysteresis: Hysteresis {
+                rising: 2,
+                falling: 4,
+                ..Default::default()
+            },
+        }],
+        filters: vec![Filter {
+            name: "Filter".into(),
+            kind: FilterKind::Ema,
+            span: 20,
+            input: Some("temp1".into()),
+        }],
+        deltas: vec![Delta {
+            name: "Delta".into(),
+            input: Some("temp1".into()),
+            reference: Some("temp2".into()),
         }],
         flats: vec![Flat {
             name: "flat1".into(),
@@ -177,6 +237,10 @@ fn config1() -> Config {
             max_temp: 70,
             max_speed: 100,
             input: Some("temp1".into()),
+            hysteresis: Hysteresis {
+                deadband: 5,
+                ..Default::default()
+            },
         }],
         targets: vec![Target {
             name: "Target".into(),
@@ -185,7 +249,55 @@ fn config1() -> Config {
             load_temp: 70,
             load_speed: 100,
             input: Some("temp3".into()),
-            idle_has_been_reatch: false,
         }],
+        pids: vec![Pid {
+            name: "Pid".into(),
+            input: Some("temp1".into()),
+            ..Default::default()
+        }],
+        mixs: vec![Mix {
+            name: "Mix".into(),
+            kind: MixKind::Max,
+            inputs: vec!["Linear".into(), "Graph".into()],
+        }],
+        switches: vec![Switch {
+            name: "Switch".into(),
+            condition: Some("temp2".into()),
+            threshold: 60,
+            hysteresis: 5,
+            input_above: Some("Pid".into()),
+            input_below: Some("Target".into()),
+        }],
+        schedules: vec![Schedule {
+            name: "Schedule".into(),
+            input: Some("Mix".into()),
+            periods: vec![
+                Period {
+                    start: TimeOfDay {
+                        hour: 22,
+                        minute: 0,
+                    },
+                    end: TimeOfDay { hour: 7, minute: 0 },
+                    input: Some("flat1".into()),
+                    ..Default::default()
+                },
+                Period {
+                    start: TimeOfDay {
+                        hour: 14,
+                        minute: 0,
+                    },
+                    end: TimeOfDay {
+                        hour: 15,
+                        minute: 30,
+                    },
+                    days: vec![Day::Mon, Day::Thu],
+                    max_speed: Some(40),
+                    ..Default::default()
+                },
+            ],
+            override_condition: Some("temp2".into()),
+            override_threshold: 85,
+        }],
+        emergency: Some(Emergency::default()),
     }
 }
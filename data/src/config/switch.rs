@@ -0,0 +1,235 @@
+use hardware::{Hardware, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_graph::Nodes,
+    id::IdGenerator,
+    node::{Input, IsValid, Node, NodeType, ToNode},
+    update::UpdateError,
+};
+
+/// Select a behavior depending on a condition: `input_above` is used once
+/// the condition is above `threshold`, until it drop below
+/// `threshold - hysteresis`. Like `Target`, but with behaviors instead of
+/// fixed speeds.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Switch {
+    pub name: String,
+    /// Sensor compared to the threshold
+    pub condition: Option<String>,
+    pub threshold: u8,
+    #[serde(default)]
+    pub hysteresis: u8,
+    #[serde(rename = "inputAbove", alias = "input_above")]
+    pub input_above: Option<String>,
+    #[serde(rename = "inputBelow", alias = "input_below")]
+    pub input_below: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SwitchState {
+    pub is_above: bool,
+}
+
+impl Switch {
+    /// Slot of the condition, the other slots are behaviors.
+    pub const CONDITION_SLOT: usize = 0;
+
+    /// Inputs by slot: the condition, then the behaviors above and below.
+    pub fn get_inputs(&self) -> Vec<Option<String>> {
+        vec![
+            self.condition.clone(),
+            self.input_above.clone(),
+            self.input_below.clone(),
+        ]
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<Option<String>>) {
+        let mut inputs = inputs.into_iter();
+        self.condition = inputs.next().flatten();
+        self.input_above = inputs.next().flatten();
+        self.input_below = inputs.next().flatten();
+    }
+
+    pub fn get_value(
+        &self,
+        state: &mut SwitchState,
+        inputs: &[Input],
+        values: &[Value],
+    ) -> Result<Value, UpdateError> {
+        let value_of = |name: &Option<String>| {
+            let name = name
+                .as_ref()
+                .ok_or_else(|| UpdateError::NodeIsInvalid(self.name.clone()))?;
+
+            inputs
+                .iter()
+                .zip(values)
+                .find(|(input, _)| &input.name == name)
+                .map(|(_, value)| *value)
+                .ok_or(UpdateError::NoInputData)
+        };
+
+        let condition = value_of(&self.condition)?;
+
+        state.is_above = if state.is_above {
+            condition >= Value::from(self.threshold) - Value::from(self.hysteresis)
+        } else {
+            condition > self.threshold.into()
+        };
+
+        if state.is_above {
+            value_of(&self.input_above)
+        } else {
+            value_of(&self.input_below)
+        }
+    }
+}
+
+impl IsValid for Switch {
+    fn is_valid(&self) -> bool {
+        self.condition.is_some() && self.input_above.is_some() && self.input_below.is_some()
+    }
+}
+
+impl ToNode for Switch {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, _hardware: &Hardware) -> Node {
+        Node::new(id_generator, NodeType::Switch(self), nodes)
+    }
+}
+
+impl Default for Switch {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            condition: Default::default(),
+            threshold: 60,
+            hysteresis: 5,
+            input_above: Default::default(),
+            input_below: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hardware::Hardware;
+
+    use crate::{
+        app_graph::Nodes,
+        config::{flat::Flat, temp::Temp},
+        id::IdGenerator,
+        node::{Input, NodeType, ToNode},
+        utils::init_test_logging,
+    };
+
+    use super::{Switch, SwitchState};
+
+    #[test]
+    fn test_update() {
+        init_test_logging();
+
+        let mut switch = Switch {
+            name: "switch".into(),
+            condition: Some("gpu".into()),
+            threshold: 60,
+            hysteresis: 5,
+            input_above: Some("curve a".into()),
+            input_below: Some("curve b".into()),
+        };
+        let mut state = SwitchState::default();
+
+        let inputs = [
+            Input {
+                id: 1,
+                name: "gpu".into(),
+            },
+            Input {
+                id: 2,
+                name: "curve a".into(),
+            },
+            Input {
+                id: 3,
+                name: "curve b".into(),
+            },
+        ];
+        let at = |temp| [temp, 80, 30];
+
+        assert!(switch.get_value(&mut state, &inputs, &at(60)).unwrap() == 30);
+        assert!(switch.get_value(&mut state, &inputs, &at(61)).unwrap() == 80);
+        // hysteresis
+        assert!(switch.get_value(&mut state, &inputs, &at(55)).unwrap() == 80);
+        assert!(switch.get_value(&mut state, &inputs, &at(54)).unwrap() == 30);
+        assert!(switch.get_value(&mut state, &inputs, &at(58)).unwrap() == 30);
+
+        // the same behavior in both slots has only one input
+        switch.set_inputs(vec![
+            Some("gpu".into()),
+            Some("curve a".into()),
+            Some("curve a".into()),
+        ]);
+        assert!(
+            switch
+                .get_value(&mut state, &inputs[..2], &[70, 80])
+                .unwrap()
+                == 80
+        );
+        assert!(
+            switch
+                .get_value(&mut state, &inputs[..2], &[20, 80])
+                .unwrap()
+                == 80
+        );
+    }
+
+    #[test]
+    fn test_slots() {
+        init_test_logging();
+
+        let mut id_generator = IdGenerator::new();
+        let mut nodes = Nodes::new();
+        let hardware = Hardware::default();
+
+        let gpu = Temp {
+            name: "gpu".into(),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        nodes.insert(gpu.id, gpu);
+
+        let quiet = Flat {
+            name: "quiet".into(),
+            value: 30,
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        nodes.insert(quiet.id, quiet);
+
+        // a sensor can't be a behavior, and a behavior can't be the condition
+        let node = Switch {
+            name: "switch".into(),
+            condition: Some("quiet".into()),
+            input_above: Some("gpu".into()),
+            input_below: Some("quiet".into()),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+
+        assert!(node.inputs.len() == 1);
+        let NodeType::Switch(switch) = node.node_type else {
+            panic!()
+        };
+        assert!(switch.get_inputs() == vec![None, None, Some("quiet".into())]);
+
+        let node = Switch {
+            name: "switch".into(),
+            condition: Some("gpu".into()),
+            input_above: Some("quiet".into()),
+            input_below: Some("quiet".into()),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+
+        assert!(node.inputs.len() == 2);
+        assert!(node.node_type.is_valid());
+    }
+}
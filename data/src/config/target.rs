@@ -19,19 +19,21 @@ pub struct Target {
     #[serde(rename = "loadSpeed", alias = "load_speed")]
     pub load_speed: u8,
     pub input: Option<String>,
+}
 
-    #[serde(skip)]
+#[derive(Debug, Clone, Default)]
+pub struct TargetState {
     pub idle_has_been_reatch: bool,
 }
 
 impl Target {
-    pub fn get_value(&mut self, value: Value) -> Result<Value, UpdateError> {
-        if self.idle_has_been_reatch {
+    pub fn get_value(&self, state: &mut TargetState, value: Value) -> Result<Value, UpdateError> {
+        if state.idle_has_been_reatch {
             if value < self.load_temp.into() {
                 return Ok(self.idle_speed.into());
             }
 
-            self.idle_has_been_reatch = false;
+            state.idle_has_been_reatch = false;
             return Ok(self.load_speed.into());
         }
 
@@ -39,7 +41,7 @@ impl Target {
             return Ok(self.load_speed.into());
         }
 
-        self.idle_has_been_reatch = true;
+        state.idle_has_been_reatch = true;
         Ok(self.idle_speed.into())
     }
 }
@@ -65,7 +67,6 @@ impl Default for Target {
             load_temp: 70,
             load_speed: 100,
             input: Default::default(),
-            idle_has_been_reatch: false,
         }
     }
 }
@@ -75,25 +76,25 @@ mod test {
 
     use crate::utils::init_test_logging;
 
-    use super::Target;
+    use super::{Target, TargetState};
 
     #[test]
     fn test_update() {
         init_test_logging();
 
-        let mut target = Target {
+        let target = Target {
             name: "linear".to_string(),
             input: Some("temp1".into()),
             idle_temp: 40,
             idle_speed: 10,
             load_temp: 70,
             load_speed: 100,
-            idle_has_been_reatch: false,
         };
+        let mut state = TargetState::default();
 
-        assert!(target.get_value(55).unwrap() == 100);
-        assert!(target.get_value(30).unwrap() == 10);
-        assert!(target.get_value(55).unwrap() == 10);
-        assert!(target.get_value(70).unwrap() == 100);
+        assert!(target.get_value(&mut state, 55).unwrap() == 100);
+        assert!(target.get_value(&mut state, 30).unwrap() == 10);
+        assert!(target.get_value(&mut state, 55).unwrap() == 10);
+        assert!(target.get_value(&mut state, 70).unwrap() == 100);
     }
 }
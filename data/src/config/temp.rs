@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
 
 use hardware::{HSensor, Hardware, HardwareBridge, Value};
 use serde::{Deserialize, Serialize};
@@ -10,22 +10,33 @@ use crate::{
     update::UpdateError,
 };
 
+use super::utils::spike_filter::{SpikeFilter, SpikeFilterState};
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Temp {
     pub name: String,
     #[serde(rename = "id")]
     pub hardware_id: Option<String>,
 
+    #[serde(flatten)]
+    pub filter: SpikeFilter,
+
     #[serde(skip)]
     pub temp_h: Option<Rc<HSensor>>,
 }
 
 impl Temp {
-    pub fn get_value<H: HardwareBridge>(&self, bridge: &mut H) -> Result<Value, UpdateError> {
+    pub fn get_value<H: HardwareBridge>(
+        &self,
+        state: &mut SpikeFilterState,
+        bridge: &mut H,
+        now: Instant,
+    ) -> Result<Value, UpdateError> {
         match &self.temp_h {
-            Some(temp_h) => bridge
-                .get_sensor_value(temp_h)
-                .map_err(UpdateError::Hardware),
+            Some(temp_h) => {
+                let value = bridge.get_sensor_value(temp_h)?;
+                self.filter.filter(state, value, now)
+            }
             None => Err(UpdateError::NodeIsInvalid(self.name.clone())),
         }
     }
@@ -33,7 +44,7 @@ impl Temp {
 
 impl IsValid for Temp {
     fn is_valid(&self) -> bool {
-        self.hardware_id.is_some() && self.temp_h.is_some()
+        self.hardware_id.is_some() && self.temp_h.is_some() && self.filter.is_valid()
     }
 }
 
@@ -0,0 +1,584 @@
+//! A small arithmetic language, used by the Expression node.
+//!
+//! Variables are node names. Names which are not simple identifiers
+//! can be quoted: `"CPU temp" - 10`. Comparisons return 1 or 0.
+//! Functions: `min`, `max`, `clamp(x, min, max)`, `abs(x)` and `if(cond, a, b)`.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("Unexpected character '{0}' at position {1}")]
+    UnexpectedChar(char, usize),
+    #[error("Unexpected end of the expression")]
+    UnexpectedEnd,
+    #[error("Unexpected \"{0}\" at position {1}")]
+    UnexpectedToken(String, usize),
+    #[error("Unknown function \"{0}\"")]
+    UnknownFunction(String),
+    #[error("Wrong number of arguments for \"{0}\"")]
+    WrongArgCount(String),
+    #[error("Unknown variable \"{0}\"")]
+    UnknownVariable(String),
+    #[error("The result is not a number")]
+    NotFinite,
+    #[error("The expression is nested too deeply")]
+    TooDeep,
+}
+
+type Result<T> = std::result::Result<T, ExprError>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Quoted(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Number(number) => number.to_string(),
+            Token::Ident(name) => name.clone(),
+            Token::Quoted(name) => format!("\"{}\"", name),
+            Token::Symbol(symbol) => symbol.to_string(),
+        }
+    }
+}
+
+// longest symbols first
+const SYMBOLS: [&str; 14] = [
+    "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "(", ")", ",", "!",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_ident_start) && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            match source[start..end].parse::<f32>() {
+                Ok(number) => Token::Number(number),
+                Err(_) => return Err(ExprError::UnexpectedChar(c, start)),
+            }
+        } else if is_ident_start(c) {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            Token::Ident(source[start..end].to_owned())
+        } else if c == '"' {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, c)) => name.push(c),
+                    None => return Err(ExprError::UnexpectedEnd),
+                }
+            }
+            Token::Quoted(name)
+        } else {
+            match SYMBOLS
+                .iter()
+                .find(|symbol| source[start..].starts_with(*symbol))
+            {
+                Some(symbol) => {
+                    for _ in 0..symbol.len() {
+                        chars.next();
+                    }
+                    Token::Symbol(symbol)
+                }
+                None => return Err(ExprError::UnexpectedChar(c, start)),
+            }
+        };
+
+        let end = chars.peek().map_or(source.len(), |(i, _)| *i);
+        tokens.push(Spanned { token, start, end });
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Min,
+    Max,
+    Clamp,
+    Abs,
+    If,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "min" => Ok(Function::Min),
+            "max" => Ok(Function::Max),
+            "clamp" => Ok(Function::Clamp),
+            "abs" => Ok(Function::Abs),
+            "if" => Ok(Function::If),
+            _ => Err(ExprError::UnknownFunction(name.to_owned())),
+        }
+    }
+
+    fn accept(&self, arg_count: usize) -> bool {
+        match self {
+            Function::Min | Function::Max => arg_count > 0,
+            Function::Clamp | Function::If => arg_count == 3,
+            Function::Abs => arg_count == 1,
+        }
+    }
+
+    /// Apply the function to its evaluated arguments, already
+    /// checked by `accept`.
+    fn apply(&self, args: &[f32]) -> f32 {
+        match self {
+            Function::Min => args.iter().copied().fold(f32::INFINITY, f32::min),
+            Function::Max => args.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            Function::Clamp => args[0].max(args[1]).min(args[2]),
+            Function::Abs => args[0].abs(),
+            Function::If => {
+                if args[0] != 0.0 {
+                    args[1]
+                } else {
+                    args[2]
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ast {
+    Number(f32),
+    Variable(String),
+    Neg(Box<Ast>),
+    Not(Box<Ast>),
+    Binary(BinOp, Box<Ast>, Box<Ast>),
+    Call(Function, Vec<Ast>),
+}
+
+/// Maximum depth of the tree, so an expression can't overflow
+/// the stack when it is parsed or evaluated.
+const MAX_DEPTH: usize = 256;
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|spanned| &spanned.token)
+    }
+
+    fn next(&mut self) -> Result<Spanned> {
+        let spanned = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        spanned.ok_or(ExprError::UnexpectedEnd)
+    }
+
+    fn eat(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<()> {
+        let spanned = self.next()?;
+        if spanned.token == Token::Symbol(symbol) {
+            Ok(())
+        } else {
+            Err(ExprError::UnexpectedToken(
+                spanned.token.text(),
+                spanned.start,
+            ))
+        }
+    }
+
+    /// Go one level deeper in the tree. The depth is not restored
+    /// on errors because they stop the parsing.
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            Err(ExprError::TooDeep)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Ast> {
+        let left = self.additive()?;
+
+        let op = match self.peek() {
+            Some(Token::Symbol("<")) => BinOp::Lt,
+            Some(Token::Symbol("<=")) => BinOp::Le,
+            Some(Token::Symbol(">")) => BinOp::Gt,
+            Some(Token::Symbol(">=")) => BinOp::Ge,
+            Some(Token::Symbol("==")) => BinOp::Eq,
+            Some(Token::Symbol("!=")) => BinOp::Ne,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+
+        let right = self.additive()?;
+        Ok(Ast::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Ast> {
+        let depth = self.depth;
+        let mut left = self.term()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("+")) => BinOp::Add,
+                Some(Token::Symbol("-")) => BinOp::Sub,
+                _ => break,
+            };
+            self.pos += 1;
+
+            // each operation nests the previous ones
+            self.enter()?;
+            let right = self.term()?;
+            left = Ast::Binary(op, Box::new(left), Box::new(right));
+        }
+
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Ast> {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("*")) => BinOp::Mul,
+                Some(Token::Symbol("/")) => BinOp::Div,
+                _ => break,
+            };
+            self.pos += 1;
+
+            // each operation nests the previous ones
+            self.enter()?;
+            let right = self.unary()?;
+            left = Ast::Binary(op, Box::new(left), Box::new(right));
+        }
+
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Ast> {
+        self.enter()?;
+
+        let ast = if self.eat("-") {
+            Ast::Neg(Box::new(self.unary()?))
+        } else if self.eat("!") {
+            Ast::Not(Box::new(self.unary()?))
+        } else {
+            self.primary()?
+        };
+
+        self.depth -= 1;
+        Ok(ast)
+    }
+
+    fn primary(&mut self) -> Result<Ast> {
+        let spanned = self.next()?;
+
+        match spanned.token {
+            Token::Number(number) => Ok(Ast::Number(number)),
+            Token::Quoted(name) => Ok(Ast::Variable(name)),
+            Token::Ident(name) => {
+                if !self.eat("(") {
+                    return Ok(Ast::Variable(name));
+                }
+
+                let function = Function::from_name(&name)?;
+
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.comparison()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+
+                if !function.accept(args.len()) {
+                    return Err(ExprError::WrongArgCount(name));
+                }
+
+                Ok(Ast::Call(function, args))
+            }
+            Token::Symbol("(") => {
+                let ast = self.comparison()?;
+                self.expect(")")?;
+                Ok(ast)
+            }
+            token => Err(ExprError::UnexpectedToken(token.text(), spanned.start)),
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Ast> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        depth: 0,
+    };
+
+    let ast = parser.comparison()?;
+
+    match parser.tokens.get(parser.pos) {
+        Some(spanned) => Err(ExprError::UnexpectedToken(
+            spanned.token.text(),
+            spanned.start,
+        )),
+        None => Ok(ast),
+    }
+}
+
+/// Rename a variable, without touching the rest of the source.
+pub fn rename_variable(source: &str, previous: &str, new: &str) -> Result<String> {
+    let tokens = tokenize(source)?;
+
+    let new = if is_ident(new) {
+        new.to_owned()
+    } else {
+        format!("\"{}\"", new)
+    };
+
+    let mut res = source.to_owned();
+
+    for (index, spanned) in tokens.iter().enumerate().rev() {
+        let name = match &spanned.token {
+            Token::Ident(name) | Token::Quoted(name) => name,
+            _ => continue,
+        };
+
+        let is_function = tokens
+            .get(index + 1)
+            .is_some_and(|next| next.token == Token::Symbol("("));
+
+        if name == previous && !is_function {
+            res.replace_range(spanned.start..spanned.end, &new);
+        }
+    }
+
+    Ok(res)
+}
+
+fn bool_value(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Ast {
+    /// Variables used, in order of appearance and without duplicates.
+    pub fn variables(&self) -> Vec<String> {
+        fn collect(ast: &Ast, variables: &mut Vec<String>) {
+            match ast {
+                Ast::Number(_) => {}
+                Ast::Variable(name) => {
+                    if !variables.contains(name) {
+                        variables.push(name.clone());
+                    }
+                }
+                Ast::Neg(ast) | Ast::Not(ast) => collect(ast, variables),
+                Ast::Binary(_, left, right) => {
+                    collect(left, variables);
+                    collect(right, variables);
+                }
+                Ast::Call(_, args) => {
+                    for arg in args {
+                        collect(arg, variables);
+                    }
+                }
+            }
+        }
+
+        let mut variables = Vec::new();
+        collect(self, &mut variables);
+        variables
+    }
+
+    pub fn eval(&self, variables: &HashMap<&str, f32>) -> Result<f32> {
+        let value = match self {
+            Ast::Number(number) => *number,
+            Ast::Variable(name) => match variables.get(name.as_str()) {
+                Some(value) => *value,
+                None => return Err(ExprError::UnknownVariable(name.clone())),
+            },
+            Ast::Neg(ast) => -ast.eval(variables)?,
+            Ast::Not(ast) => bool_value(ast.eval(variables)? == 0.0),
+            Ast::Binary(op, left, right) => {
+                let left = left.eval(variables)?;
+                let right = right.eval(variables)?;
+                match op {
+                    BinOp::Add => left + right,
+                    BinOp::Sub => left - right,
+                    BinOp::Mul => left * right,
+                    BinOp::Div => left / right,
+                    BinOp::Lt => bool_value(left < right),
+                    BinOp::Le => bool_value(left <= right),
+                    BinOp::Gt => bool_value(left > right),
+                    BinOp::Ge => bool_value(left >= right),
+                    BinOp::Eq => bool_value(left == right),
+                    BinOp::Ne => bool_value(left != right),
+                }
+            }
+            // only the selected branch is evaluated
+            Ast::Call(Function::If, args) => {
+                if args[0].eval(variables)? != 0.0 {
+                    args[1].eval(variables)?
+                } else {
+                    args[2].eval(variables)?
+                }
+            }
+            Ast::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(variables))
+                    .collect::<Result<Vec<_>>>()?;
+
+                function.apply(&args)
+            }
+        };
+
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(ExprError::NotFinite)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::utils::init_test_logging;
+
+    use super::{parse, rename_variable, ExprError};
+
+    #[test]
+    fn test_eval() {
+        init_test_logging();
+
+        let variables = HashMap::from([("cpu", 60.0), ("gpu", 75.0), ("case temp", 30.0)]);
+        let eval = |source: &str| parse(source).unwrap().eval(&variables);
+
+        assert!(eval("max(cpu, gpu - 10) * 1.5").unwrap() == 97.5);
+        assert!(eval("1 + 2 * 3 - -1").unwrap() == 8.0);
+        assert!(eval("(1 + 2) * 3").unwrap() == 9.0);
+        assert!(eval("clamp(\"case temp\" * 4, 20, 100)").unwrap() == 100.0);
+        assert!(eval("if(cpu >= 60, 100, abs(-20))").unwrap() == 100.0);
+        assert!(eval("if(!(cpu < gpu), 100, 20)").unwrap() == 20.0);
+        assert!(eval("min(cpu)").unwrap() == 60.0);
+
+        assert!(eval("cpu / 0") == Err(ExprError::NotFinite));
+        assert!(eval("ambient") == Err(ExprError::UnknownVariable("ambient".into())));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        init_test_logging();
+
+        assert!(parse("") == Err(ExprError::UnexpectedEnd));
+        assert!(parse("cpu +") == Err(ExprError::UnexpectedEnd));
+        assert!(parse("cpu gpu") == Err(ExprError::UnexpectedToken("gpu".into(), 4)));
+        assert!(parse("cpu $ 2") == Err(ExprError::UnexpectedChar('$', 4)));
+        assert!(parse("sqrt(cpu)") == Err(ExprError::UnknownFunction("sqrt".into())));
+        assert!(parse("clamp(cpu, 1)") == Err(ExprError::WrongArgCount("clamp".into())));
+        assert!(parse("(cpu") == Err(ExprError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        init_test_logging();
+
+        let nested = |depth: usize| format!("{}cpu{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(100)).is_ok());
+        assert!(parse(&nested(100_000)) == Err(ExprError::TooDeep));
+
+        assert!(parse(&format!("{}cpu", "-".repeat(100_000))) == Err(ExprError::TooDeep));
+        assert!(parse(&format!("{}cpu", "abs(".repeat(100_000))) == Err(ExprError::TooDeep));
+        assert!(parse(&"cpu + ".repeat(100_000)) == Err(ExprError::TooDeep));
+        assert!(parse(&format!("{}cpu", "cpu * ".repeat(100))).is_ok());
+    }
+
+    #[test]
+    fn test_variables_and_rename() {
+        init_test_logging();
+
+        let ast = parse("max(cpu, \"gpu 1\") - cpu").unwrap();
+        assert!(ast.variables() == vec!["cpu".to_string(), "gpu 1".to_string()]);
+
+        let renamed = rename_variable("max(cpu, max) +  cpu", "max", "gpu 2").unwrap();
+        assert!(renamed == "max(cpu, \"gpu 2\") +  cpu");
+
+        let renamed = rename_variable("\"gpu 2\" - 1", "gpu 2", "gpu").unwrap();
+        assert!(renamed == "gpu - 1");
+    }
+}
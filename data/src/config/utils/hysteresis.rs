@@ -0,0 +1,113 @@
+use hardware::Value;
+use serde::{Deserialize, Serialize};
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+/// Prevent a behavior from hunting when the temp hover around a point.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Hysteresis {
+    /// The temp must rise by this much (°C) before the output follow it
+    #[serde(
+        default,
+        rename = "risingHysteresis",
+        alias = "rising_hysteresis",
+        skip_serializing_if = "is_zero"
+    )]
+    pub rising: u8,
+    /// The temp must fall by this much (°C) before the output follow it
+    #[serde(
+        default,
+        rename = "fallingHysteresis",
+        alias = "falling_hysteresis",
+        skip_serializing_if = "is_zero"
+    )]
+    pub falling: u8,
+    /// Minimum change of the output (%) before it is applied
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub deadband: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HysteresisState {
+    pub last_temp: Option<Value>,
+    pub last_output: Option<Value>,
+}
+
+impl Hysteresis {
+    /// Return the temp that should be used to compute the output.
+    pub fn temp(&self, state: &mut HysteresisState, value: Value) -> Value {
+        let temp = match state.last_temp {
+            Some(last) if value > last && value - last < self.rising.into() => last,
+            Some(last) if value < last && last - value < self.falling.into() => last,
+            _ => value,
+        };
+
+        state.last_temp = Some(temp);
+        temp
+    }
+
+    /// Return the output that should be applied. Reaching `min` or `max`
+    /// is always allowed, so the deadband can't prevent a full speed.
+    pub fn output(
+        &self,
+        state: &mut HysteresisState,
+        output: Value,
+        min: Value,
+        max: Value,
+    ) -> Value {
+        if let Some(last) = state.last_output {
+            let is_limit = output <= min || output >= max;
+            if (output - last).abs() < self.deadband.into() && !is_limit {
+                return last;
+            }
+        }
+
+        state.last_output = Some(output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::init_test_logging;
+
+    use super::{Hysteresis, HysteresisState};
+
+    #[test]
+    fn test_temp() {
+        init_test_logging();
+
+        let hysteresis = Hysteresis {
+            rising: 2,
+            falling: 4,
+            ..Default::default()
+        };
+        let mut state = HysteresisState::default();
+
+        assert!(hysteresis.temp(&mut state, 50) == 50);
+        assert!(hysteresis.temp(&mut state, 51) == 50);
+        assert!(hysteresis.temp(&mut state, 52) == 52);
+        assert!(hysteresis.temp(&mut state, 49) == 52);
+        assert!(hysteresis.temp(&mut state, 48) == 48);
+    }
+
+    #[test]
+    fn test_deadband() {
+        init_test_logging();
+
+        let hysteresis = Hysteresis {
+            deadband: 5,
+            ..Default::default()
+        };
+        let mut state = HysteresisState::default();
+
+        assert!(hysteresis.output(&mut state, 50, 10, 100) == 50);
+        assert!(hysteresis.output(&mut state, 53, 10, 100) == 50);
+        assert!(hysteresis.output(&mut state, 46, 10, 100) == 50);
+        assert!(hysteresis.output(&mut state, 55, 10, 100) == 55);
+        assert!(hysteresis.output(&mut state, 97, 10, 100) == 97);
+        assert!(hysteresis.output(&mut state, 100, 10, 100) == 100);
+    }
+}
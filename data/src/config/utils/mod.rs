@@ -1 +1,5 @@
 pub mod affine;
+pub mod expr;
+pub mod hysteresis;
+pub mod spike_filter;
+pub mod spline;
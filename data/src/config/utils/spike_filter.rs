@@ -0,0 +1,147 @@
+use std::{collections::VecDeque, time::Instant};
+
+use hardware::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::update::UpdateError;
+
+/// Reject implausible values and spikes that some drivers report for one sample.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SpikeFilter {
+    /// Values under this limit are rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Value>,
+    /// Values above this limit are rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Value>,
+    /// Maximum change per second, compared to the last accepted value
+    #[serde(
+        default,
+        rename = "maxDelta",
+        alias = "max_delta",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_delta: Option<Value>,
+    /// Return the median of the last N accepted values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpikeFilterState {
+    pub last_accepted: Option<(Value, Instant)>,
+    pub samples: VecDeque<Value>,
+}
+
+impl SpikeFilter {
+    pub fn is_valid(&self) -> bool {
+        let limits_are_valid = match (self.min, self.max) {
+            (Some(min), Some(max)) => min < max,
+            _ => true,
+        };
+
+        limits_are_valid && !matches!(self.max_delta, Some(..=0)) && self.median != Some(0)
+    }
+
+    fn is_plausible(&self, value: Value) -> bool {
+        let too_low = self.min.is_some_and(|min| value < min);
+        let too_high = self.max.is_some_and(|max| value > max);
+        !(too_low || too_high)
+    }
+
+    fn is_spike(&self, state: &SpikeFilterState, value: Value, now: Instant) -> bool {
+        match (self.max_delta, state.last_accepted) {
+            (Some(max_delta), Some((last_value, last_time))) => {
+                let elapsed = now.duration_since(last_time).as_secs_f32();
+                (value - last_value).abs() as f32 > max_delta as f32 * elapsed
+            }
+            _ => false,
+        }
+    }
+
+    fn current(state: &SpikeFilterState) -> Option<Value> {
+        if state.samples.is_empty() {
+            return state.last_accepted.map(|(value, _)| value);
+        }
+
+        let mut sorted = state.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    /// Return the filtered value. A rejected value is replaced by the
+    /// previous output, if there is one.
+    pub fn filter(
+        &self,
+        state: &mut SpikeFilterState,
+        value: Value,
+        now: Instant,
+    ) -> Result<Value, UpdateError> {
+        if !self.is_plausible(value) || self.is_spike(state, value, now) {
+            debug!("Value {} rejected by the spike filter.", value);
+            return Self::current(state).ok_or(UpdateError::RejectedValue(value));
+        }
+
+        state.last_accepted = Some((value, now));
+
+        if let Some(median) = self.median {
+            state.samples.push_back(value);
+            while state.samples.len() > median.into() {
+                state.samples.pop_front();
+            }
+        }
+
+        Ok(Self::current(state).unwrap_or(value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::utils::init_test_logging;
+
+    use super::{SpikeFilter, SpikeFilterState};
+
+    #[test]
+    fn test_limits_and_delta() {
+        init_test_logging();
+
+        let filter = SpikeFilter {
+            min: Some(0),
+            max: Some(110),
+            max_delta: Some(10),
+            ..Default::default()
+        };
+        let mut state = SpikeFilterState::default();
+
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(filter.filter(&mut state, 127, at(0)).is_err());
+        assert!(filter.filter(&mut state, 40, at(0)).unwrap() == 40);
+        assert!(filter.filter(&mut state, -128, at(1)).unwrap() == 40);
+        assert!(filter.filter(&mut state, 90, at(2)).unwrap() == 40);
+        assert!(filter.filter(&mut state, 45, at(3)).unwrap() == 45);
+        // a real jump is accepted once enough time has passed
+        assert!(filter.filter(&mut state, 80, at(7)).unwrap() == 80);
+    }
+
+    #[test]
+    fn test_median() {
+        init_test_logging();
+
+        let filter = SpikeFilter {
+            median: Some(3),
+            ..Default::default()
+        };
+        let mut state = SpikeFilterState::default();
+
+        let now = Instant::now();
+
+        assert!(filter.filter(&mut state, 1200, now).unwrap() == 1200);
+        assert!(filter.filter(&mut state, 1210, now).unwrap() == 1210);
+        assert!(filter.filter(&mut state, 0, now).unwrap() == 1200);
+        assert!(filter.filter(&mut state, 1220, now).unwrap() == 1210);
+    }
+}
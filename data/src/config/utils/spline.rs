@@ -0,0 +1,98 @@
+/// Monotone cubic interpolation (Fritsch-Carlson). Unlike a natural
+/// cubic spline, it never overshoot the points, so a fan curve
+/// that only rise will not drop between two points.
+///
+/// `points` must be sorted by x, without duplicate. Outside of the
+/// points, the value of the nearest one is returned.
+pub fn monotone_cubic(points: &[(f32, f32)], x: f32) -> f32 {
+    let n = points.len();
+
+    match n {
+        0 => return 0.0,
+        1 => return points[0].1,
+        _ => {}
+    }
+
+    if x <= points[0].0 {
+        return points[0].1;
+    }
+    if x >= points[n - 1].0 {
+        return points[n - 1].1;
+    }
+
+    let secants: Vec<f32> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for k in 1..n - 1 {
+        if secants[k - 1] * secants[k] > 0.0 {
+            tangents[k] = (secants[k - 1] + secants[k]) / 2.0;
+        }
+    }
+
+    // limit the tangents so the curve stay monotone
+    for k in 0..n - 1 {
+        if secants[k] == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+
+        let a = tangents[k] / secants[k];
+        let b = tangents[k + 1] / secants[k];
+        let norm = a * a + b * b;
+        if norm > 9.0 {
+            let t = 3.0 / norm.sqrt();
+            tangents[k] = t * a * secants[k];
+            tangents[k + 1] = t * b * secants[k];
+        }
+    }
+
+    let k = points.partition_point(|(px, _)| *px <= x) - 1;
+    let (x0, y0) = points[k];
+    let (x1, y1) = points[k + 1];
+    let h = x1 - x0;
+    let t = (x - x0) / h;
+
+    let h00 = (1.0 + 2.0 * t) * (1.0 - t).powi(2);
+    let h10 = t * (1.0 - t).powi(2);
+    let h01 = t * t * (3.0 - 2.0 * t);
+    let h11 = t * t * (t - 1.0);
+
+    h00 * y0 + h10 * h * tangents[k] + h01 * y1 + h11 * h * tangents[k + 1]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::init_test_logging;
+
+    use super::monotone_cubic;
+
+    #[test]
+    fn test_monotone_cubic() {
+        init_test_logging();
+
+        let points = [(20.0, 20.0), (40.0, 30.0), (60.0, 80.0), (80.0, 100.0)];
+
+        assert!(monotone_cubic(&points, 10.0) == 20.0);
+        assert!(monotone_cubic(&points, 40.0) == 30.0);
+        assert!(monotone_cubic(&points, 90.0) == 100.0);
+
+        // never decrease, and stay between the points
+        let mut previous = 20.0;
+        for x in 20..=80 {
+            let y = monotone_cubic(&points, x as f32);
+            assert!(y >= previous);
+            assert!(y <= 100.0);
+            previous = y;
+        }
+
+        // flat parts stay flat
+        let points = [(20.0, 50.0), (40.0, 50.0), (60.0, 100.0)];
+        assert!(monotone_cubic(&points, 30.0) == 50.0);
+    }
+}
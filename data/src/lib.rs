@@ -5,23 +5,79 @@ extern crate log;
 pub mod localize;
 
 pub mod app_graph;
+pub mod auto_switch;
 pub mod config;
 pub mod dir_manager;
 pub mod id;
 pub mod node;
 pub mod settings;
+pub mod units;
 pub mod update;
 pub mod utils;
 
+use std::time::Instant;
+
 use crate::app_graph::AppGraph;
+use auto_switch::AutoSwitcher;
 use hardware::HardwareBridge;
 use update::Update;
 
-use crate::dir_manager::DirManager;
+use crate::dir_manager::{ConfigError, DirManager};
 
 pub struct AppState<H: HardwareBridge> {
     pub dir_manager: DirManager,
     pub bridge: H,
     pub app_graph: AppGraph,
     pub update: Update,
+    pub auto_switcher: AutoSwitcher,
+}
+
+impl<H: HardwareBridge> AppState<H> {
+    /// Give the controls back to the hardware, then load the config.
+    /// Return the name of the config loaded.
+    pub fn change_config(
+        &mut self,
+        config_name: Option<String>,
+    ) -> Result<Option<String>, ConfigError> {
+        if config_name.is_some() {
+            self.update.set_valid_root_nodes_to_auto(
+                &mut self.app_graph.nodes,
+                &self.app_graph.root_nodes,
+                &mut self.bridge,
+            );
+        }
+
+        match self.dir_manager.change_config(config_name)? {
+            Some((config_name, config)) => {
+                self.app_graph = AppGraph::from_config(config, self.bridge.hardware());
+                self.update.invalidate_plan();
+                Ok(Some(config_name))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Change the config if the rules of the settings ask for it.
+    /// Return the name of the config loaded.
+    pub fn auto_switch_config(&mut self) -> Option<String> {
+        let settings = self.dir_manager.settings();
+        let auto_switch = settings.auto_switch.as_ref()?;
+        let config_names = &self.dir_manager.config_names;
+
+        let config_name = self.auto_switcher.poll(
+            auto_switch,
+            settings.current_config.as_deref(),
+            |name| config_names.contains(name),
+            Instant::now(),
+        )?;
+
+        info!("Auto switch to config {}.", config_name);
+        match self.change_config(Some(config_name)) {
+            Ok(config_name) => config_name,
+            Err(e) => {
+                error!("Can't change config: {}.", e);
+                None
+            }
+        }
+    }
 }
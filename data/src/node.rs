@@ -8,11 +8,27 @@ use light_enum::LightEnum;
 use crate::app_graph::Nodes;
 
 use crate::config::{
-    control::Control, custom_temp::CustomTemp, fan::Fan, flat::Flat, graph::Graph, linear::Linear,
-    target::Target, temp::Temp,
+    control::{Control, ControlState},
+    custom_temp::CustomTemp,
+    delta::Delta,
+    expression::Expression,
+    fan::Fan,
+    filter::{Filter, FilterState},
+    flat::Flat,
+    graph::Graph,
+    linear::Linear,
+    metric::Metric,
+    mix::Mix,
+    pid::{Pid, PidState},
+    schedule::Schedule,
+    switch::{Switch, SwitchState},
+    target::{Target, TargetState},
+    temp::Temp,
+    utils::{hysteresis::HysteresisState, spike_filter::SpikeFilterState},
 };
 
 use crate::id::{Id, IdGenerator};
+use crate::units::Units;
 
 #[derive(Debug, Clone, LightEnum, Unwrap)]
 #[unwrap(ref, ref_mut)]
@@ -20,11 +36,19 @@ pub enum NodeType {
     Control(Control),
     Fan(Fan),
     Temp(Temp),
+    Metric(Metric),
     CustomTemp(CustomTemp),
     Graph(Graph),
     Flat(Flat),
     Linear(Linear),
     Target(Target),
+    Pid(Pid),
+    Filter(Filter),
+    Delta(Delta),
+    Expression(Expression),
+    Mix(Mix),
+    Switch(Switch),
+    Schedule(Schedule),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,12 +69,54 @@ pub struct Node {
     pub node_type: NodeType,
     pub inputs: Vec<Input>,
     pub value: Option<Value>,
+    pub state: NodeState,
+}
+
+/// Runtime state of a node, kept between updates. Unlike the
+/// node type, it is not part of the config, so it is never saved.
+#[derive(Debug, Clone, Default)]
+pub enum NodeState {
+    #[default]
+    None,
+    Control(ControlState),
+    Fan(SpikeFilterState),
+    Temp(SpikeFilterState),
+    Graph(HysteresisState),
+    Linear(HysteresisState),
+    Target(TargetState),
+    Pid(PidState),
+    Filter(FilterState),
+    Switch(SwitchState),
+}
+
+impl NodeState {
+    pub fn new(node_type: &NodeType) -> Self {
+        match node_type {
+            NodeType::Control(_) => NodeState::Control(Default::default()),
+            NodeType::Fan(_) => NodeState::Fan(Default::default()),
+            NodeType::Temp(_) => NodeState::Temp(Default::default()),
+            NodeType::Graph(_) => NodeState::Graph(Default::default()),
+            NodeType::Linear(_) => NodeState::Linear(Default::default()),
+            NodeType::Target(_) => NodeState::Target(Default::default()),
+            NodeType::Pid(_) => NodeState::Pid(Default::default()),
+            NodeType::Filter(_) => NodeState::Filter(Default::default()),
+            NodeType::Switch(_) => NodeState::Switch(Default::default()),
+            NodeType::Metric(_)
+            | NodeType::CustomTemp(_)
+            | NodeType::Flat(_)
+            | NodeType::Delta(_)
+            | NodeType::Expression(_)
+            | NodeType::Mix(_)
+            | NodeType::Schedule(_) => NodeState::None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Display)]
 pub enum NbInput {
     Zero,
     One,
+    Two,
     Infinity,
 }
 
@@ -58,7 +124,7 @@ pub enum NbInput {
 pub struct Sanitize {
     pub id: Id,
     node: Vec<Input>,
-    item: Vec<String>,
+    item: Vec<Option<String>>,
 }
 
 impl Sanitize {
@@ -71,43 +137,62 @@ impl Sanitize {
     }
 
     fn add(&mut self, id: Id, name: &str) {
-        self.item.push(name.to_owned());
+        self.item.push(Some(name.to_owned()));
+
+        // the same node can be used in several slots, its value
+        // is then given to each of them when updating
+        if self.node.iter().any(|i| i.id == id) {
+            return;
+        }
         let input = Input {
             id,
             name: name.to_owned(),
         };
         self.node.push(input);
     }
+
+    /// Keep the slot, so the position of the next inputs doesn't change.
+    fn add_none(&mut self) {
+        self.item.push(None);
+    }
 }
 
 pub fn sanitize_inputs(node: &Node, nodes: &Nodes, log: bool) -> Sanitize {
     let mut sanitize = Sanitize::new(node.id);
 
-    match node.node_type.max_input() {
+    let max = match node.node_type.max_input() {
         NbInput::Zero => {
             return sanitize;
         }
-        NbInput::One => {
-            if node.inputs.len() > 1 || node.node_type.get_inputs().len() > 1 {
-                // todo: remove this debug print
-                error!(
-                    "sanitize_inputs {}: {:?} number of dep != {}",
-                    node.name(),
-                    node.node_type.to_light(),
-                    node.node_type.max_input()
-                );
-                return sanitize;
-            }
-        }
-        NbInput::Infinity => {}
+        NbInput::One => Some(1),
+        NbInput::Two => Some(2),
+        NbInput::Infinity => None,
     };
 
-    for name in node.node_type.get_inputs() {
+    if let Some(max) = max {
+        if node.inputs.len() > max || node.node_type.get_inputs().len() > max {
+            // todo: remove this debug print
+            error!(
+                "sanitize_inputs {}: {:?} number of dep != {}",
+                node.name(),
+                node.node_type.to_light(),
+                node.node_type.max_input()
+            );
+            return sanitize;
+        }
+    }
+
+    for (slot, name) in node.node_type.get_inputs().into_iter().enumerate() {
+        let Some(name) = name else {
+            sanitize.add_none();
+            continue;
+        };
+
         match nodes.values().find(|n| n.name() == &name) {
             Some(n) => {
                 match node
                     .node_type
-                    .allowed_dep()
+                    .allowed_dep_at(slot)
                     .contains(&n.node_type.to_light())
                 {
                     true => {
@@ -120,6 +205,7 @@ pub fn sanitize_inputs(node: &Node, nodes: &Nodes, log: bool) -> Sanitize {
                             name,
                             n.node_type.to_light(),
                         );
+                        sanitize.add_none();
                     }
                 }
             }
@@ -127,6 +213,7 @@ pub fn sanitize_inputs(node: &Node, nodes: &Nodes, log: bool) -> Sanitize {
                 if log {
                     warn!("sanitize_inputs {}: can't find node {}", node.name(), name);
                 }
+                sanitize.add_none();
             }
         }
     }
@@ -145,6 +232,29 @@ pub trait ToNode {
     fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, hardware: &Hardware) -> Node;
 }
 
+impl ToNode for NodeType {
+    fn to_node(self, id_generator: &mut IdGenerator, nodes: &Nodes, hardware: &Hardware) -> Node {
+        match self {
+            NodeType::Control(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Fan(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Temp(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Metric(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::CustomTemp(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Graph(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Flat(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Linear(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Target(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Pid(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Filter(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Delta(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Expression(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Mix(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Switch(i) => i.to_node(id_generator, nodes, hardware),
+            NodeType::Schedule(i) => i.to_node(id_generator, nodes, hardware),
+        }
+    }
+}
+
 pub trait IsValid {
     fn is_valid(&self) -> bool;
 }
@@ -153,6 +263,7 @@ impl Node {
     pub fn new(id_generator: &mut IdGenerator, node_type: NodeType, nodes: &Nodes) -> Self {
         let mut node = Self {
             id: id_generator.new_id(),
+            state: NodeState::new(&node_type),
             node_type,
             inputs: Vec::new(),
             value: None,
@@ -177,17 +288,14 @@ impl Node {
             NodeType::Control(i) => &i.hardware_id,
             NodeType::Fan(i) => &i.hardware_id,
             NodeType::Temp(i) => &i.hardware_id,
+            NodeType::Metric(i) => &i.hardware_id,
             _ => panic!(),
         }
     }
 
-    pub fn value_text(&self, kind: &ValueKind) -> String {
+    pub fn value_text(&self, kind: &ValueKind, units: &Units) -> String {
         match self.value {
-            Some(val) => match kind {
-                ValueKind::Celsius => fl!("value_celsius", value = val),
-                ValueKind::Porcentage => fl!("value_percentage", value = val),
-                ValueKind::RPM => fl!("value_rpm", value = val),
-            },
+            Some(val) => units.value_text(val, kind),
             None => fl!("no_value"),
         }
     }
@@ -195,12 +303,45 @@ impl Node {
     pub fn is_root(&self) -> bool {
         self.node_type.is_root()
     }
+
+    /// Kind of the value of the node. A filter keep the kind of its input.
+    pub fn value_kind(&self, nodes: &Nodes) -> ValueKind {
+        match &self.node_type {
+            NodeType::Fan(_) => ValueKind::RPM,
+            NodeType::Temp(_) | NodeType::CustomTemp(_) => ValueKind::Celsius,
+            NodeType::Metric(_) => ValueKind::Load,
+            NodeType::Delta(_) => ValueKind::TempDelta,
+            NodeType::Expression(_) => ValueKind::Unitless,
+            NodeType::Filter(_) => self
+                .inputs
+                .first()
+                .and_then(|input| nodes.get(&input.id))
+                .map_or(ValueKind::Celsius, |input| input.value_kind(nodes)),
+            NodeType::Control(_)
+            | NodeType::Graph(_)
+            | NodeType::Flat(_)
+            | NodeType::Linear(..)
+            | NodeType::Target(..)
+            | NodeType::Pid(_)
+            | NodeType::Mix(_)
+            | NodeType::Switch(_)
+            | NodeType::Schedule(_) => ValueKind::Porcentage,
+        }
+    }
 }
 
+/// Kind of a value, in the unit stored in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueKind {
     Celsius,
+    /// Difference between two temps, in °C
+    TempDelta,
+    /// Speed, in %
     Porcentage,
+    /// Utilization of the system, in %. Never converted.
+    Load,
     RPM,
+    Unitless,
 }
 
 impl NodeType {
@@ -209,11 +350,19 @@ impl NodeType {
             NodeType::Control(control) => &control.name,
             NodeType::Fan(fan) => &fan.name,
             NodeType::Temp(temp) => &temp.name,
+            NodeType::Metric(metric) => &metric.name,
             NodeType::CustomTemp(custom_temp) => &custom_temp.name,
             NodeType::Graph(graph) => &graph.name,
             NodeType::Flat(flat) => &flat.name,
             NodeType::Linear(linear, ..) => &linear.name,
             NodeType::Target(target, ..) => &target.name,
+            NodeType::Pid(pid) => &pid.name,
+            NodeType::Filter(filter) => &filter.name,
+            NodeType::Delta(delta) => &delta.name,
+            NodeType::Expression(expression) => &expression.name,
+            NodeType::Mix(mix) => &mix.name,
+            NodeType::Switch(switch) => &switch.name,
+            NodeType::Schedule(schedule) => &schedule.name,
         }
     }
     pub fn set_name(&mut self, name: &str) {
@@ -222,11 +371,19 @@ impl NodeType {
             NodeType::Control(i) => i.name = name_cloned,
             NodeType::Fan(i) => i.name = name_cloned,
             NodeType::Temp(i) => i.name = name_cloned,
+            NodeType::Metric(i) => i.name = name_cloned,
             NodeType::CustomTemp(i) => i.name = name_cloned,
             NodeType::Graph(i) => i.name = name_cloned,
             NodeType::Flat(i) => i.name = name_cloned,
             NodeType::Linear(i, ..) => i.name = name_cloned,
             NodeType::Target(i, ..) => i.name = name_cloned,
+            NodeType::Pid(i) => i.name = name_cloned,
+            NodeType::Filter(i) => i.name = name_cloned,
+            NodeType::Delta(i) => i.name = name_cloned,
+            NodeType::Expression(i) => i.name = name_cloned,
+            NodeType::Mix(i) => i.name = name_cloned,
+            NodeType::Switch(i) => i.name = name_cloned,
+            NodeType::Schedule(i) => i.name = name_cloned,
         }
     }
 
@@ -235,66 +392,73 @@ impl NodeType {
             NodeType::Control(control) => control.is_valid(),
             NodeType::Fan(fan) => fan.is_valid(),
             NodeType::Temp(temp) => temp.is_valid(),
+            NodeType::Metric(metric) => metric.is_valid(),
             NodeType::CustomTemp(custom_temp) => custom_temp.is_valid(),
             NodeType::Graph(graph) => graph.is_valid(),
             NodeType::Flat(flat) => flat.is_valid(),
             NodeType::Linear(linear, ..) => linear.is_valid(),
             NodeType::Target(target, ..) => target.is_valid(),
+            NodeType::Pid(pid) => pid.is_valid(),
+            NodeType::Filter(filter) => filter.is_valid(),
+            NodeType::Delta(delta) => delta.is_valid(),
+            NodeType::Expression(expression) => expression.is_valid(),
+            NodeType::Mix(mix) => mix.is_valid(),
+            NodeType::Switch(switch) => switch.is_valid(),
+            NodeType::Schedule(schedule) => schedule.is_valid(),
         }
     }
 
-    pub fn get_inputs(&self) -> Vec<String> {
+    /// Inputs by slot. A slot is None when no node is selected, or when
+    /// the node was not found.
+    pub fn get_inputs(&self) -> Vec<Option<String>> {
         match self {
-            NodeType::Control(i) => i.input.clone().map_or(Vec::new(), |i| vec![i]),
+            NodeType::Control(i) => vec![i.input.clone()],
             NodeType::Fan(_) => Vec::new(),
             NodeType::Temp(_) => Vec::new(),
-            NodeType::CustomTemp(i) => i.inputs.clone(),
-            NodeType::Graph(i) => i.input.clone().map_or(Vec::new(), |i| vec![i]),
+            NodeType::Metric(_) => Vec::new(),
+            NodeType::CustomTemp(i) => i.inputs.iter().cloned().map(Some).collect(),
+            NodeType::Graph(i) => vec![i.input.clone()],
             NodeType::Flat(_) => Vec::new(),
-            NodeType::Linear(i, ..) => i.input.clone().map_or(Vec::new(), |i| vec![i]),
-            NodeType::Target(i, ..) => i.input.clone().map_or(Vec::new(), |i| vec![i]),
+            NodeType::Linear(i, ..) => vec![i.input.clone()],
+            NodeType::Target(i, ..) => vec![i.input.clone()],
+            NodeType::Pid(i) => vec![i.input.clone()],
+            NodeType::Filter(i) => vec![i.input.clone()],
+            NodeType::Expression(i) => i.get_inputs().into_iter().map(Some).collect(),
+            NodeType::Mix(i) => i.inputs.iter().cloned().map(Some).collect(),
+            NodeType::Switch(i) => i.get_inputs(),
+            NodeType::Schedule(i) => i.get_inputs(),
+            NodeType::Delta(i) => vec![i.input.clone(), i.reference.clone()],
         }
     }
 
-    pub fn set_inputs(&mut self, inputs: Vec<String>) {
+    /// Inverse of `get_inputs`, slot positions are kept.
+    pub fn set_inputs(&mut self, inputs: Vec<Option<String>>) {
+        let first = || inputs.first().cloned().flatten();
+
         match self {
-            NodeType::Control(i) => match inputs.first() {
-                Some(input) => {
-                    let _ = i.input.insert(input.clone());
-                }
-                None => {
-                    i.input.take();
-                }
-            },
+            NodeType::Control(i) => i.input = first(),
             NodeType::CustomTemp(i) => {
-                i.inputs = inputs;
+                i.inputs = inputs.into_iter().flatten().collect();
+            }
+            NodeType::Graph(i) => i.input = first(),
+            NodeType::Linear(i, ..) => i.input = first(),
+            NodeType::Target(i, ..) => i.input = first(),
+            NodeType::Pid(i) => i.input = first(),
+            NodeType::Filter(i) => i.input = first(),
+            NodeType::Expression(i) => i.set_inputs(inputs),
+            NodeType::Mix(i) => {
+                i.inputs = inputs.into_iter().flatten().collect();
+            }
+            NodeType::Switch(i) => i.set_inputs(inputs),
+            NodeType::Schedule(i) => i.set_inputs(inputs),
+            NodeType::Delta(i) => {
+                let mut inputs = inputs.into_iter();
+                i.input = inputs.next().flatten();
+                i.reference = inputs.next().flatten();
             }
-            NodeType::Graph(i) => match inputs.first() {
-                Some(input) => {
-                    let _ = i.input.insert(input.clone());
-                }
-                None => {
-                    i.input.take();
-                }
-            },
-            NodeType::Linear(i, ..) => match inputs.first() {
-                Some(input) => {
-                    let _ = i.input.insert(input.clone());
-                }
-                None => {
-                    i.input.take();
-                }
-            },
-            NodeType::Target(i, ..) => match inputs.first() {
-                Some(input) => {
-                    let _ = i.input.insert(input.clone());
-                }
-                None => {
-                    i.input.take();
-                }
-            },
             NodeType::Fan(_) => {}
             NodeType::Temp(_) => {}
+            NodeType::Metric(_) => {}
             NodeType::Flat(_) => {}
         };
     }
@@ -306,14 +470,146 @@ impl NodeType {
                 NodeTypeLight::Graph,
                 NodeTypeLight::Target,
                 NodeTypeLight::Linear,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Expression,
+                NodeTypeLight::Mix,
+                NodeTypeLight::Switch,
+                NodeTypeLight::Schedule,
             ],
             NodeType::Fan(..) => &[],
             NodeType::Temp(..) => &[],
+            NodeType::Metric(..) => &[],
             NodeType::CustomTemp(..) => &[NodeTypeLight::Temp],
-            NodeType::Graph(..) => &[NodeTypeLight::Temp, NodeTypeLight::CustomTemp],
+            NodeType::Graph(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
             NodeType::Flat(..) => &[],
-            NodeType::Linear(..) => &[NodeTypeLight::Temp, NodeTypeLight::CustomTemp],
-            NodeType::Target(..) => &[NodeTypeLight::Temp, NodeTypeLight::CustomTemp],
+            NodeType::Linear(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
+            NodeType::Target(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
+            NodeType::Pid(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
+            NodeType::Filter(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+            ],
+            NodeType::Delta(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+            ],
+            NodeType::Expression(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Fan,
+                NodeTypeLight::Flat,
+            ],
+            NodeType::Mix(..) => &[
+                NodeTypeLight::Graph,
+                NodeTypeLight::Flat,
+                NodeTypeLight::Linear,
+                NodeTypeLight::Target,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Switch,
+            ],
+            NodeType::Switch(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+                NodeTypeLight::Graph,
+                NodeTypeLight::Flat,
+                NodeTypeLight::Linear,
+                NodeTypeLight::Target,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Mix,
+            ],
+            NodeType::Schedule(..) => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+                NodeTypeLight::Graph,
+                NodeTypeLight::Flat,
+                NodeTypeLight::Linear,
+                NodeTypeLight::Target,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Mix,
+                NodeTypeLight::Switch,
+            ],
+        }
+    }
+
+    /// Like `allowed_dep`, for a single slot. Nodes with inputs of
+    /// different kinds restrict each slot to a part of `allowed_dep`.
+    pub fn allowed_dep_at(&self, slot: usize) -> &'static [NodeTypeLight] {
+        match self {
+            NodeType::Switch(..) if slot == Switch::CONDITION_SLOT => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
+            NodeType::Schedule(..) if slot == Schedule::OVERRIDE_SLOT => &[
+                NodeTypeLight::Temp,
+                NodeTypeLight::Metric,
+                NodeTypeLight::CustomTemp,
+                NodeTypeLight::Filter,
+                NodeTypeLight::Delta,
+                NodeTypeLight::Expression,
+            ],
+            NodeType::Schedule(..) => &[
+                NodeTypeLight::Graph,
+                NodeTypeLight::Flat,
+                NodeTypeLight::Linear,
+                NodeTypeLight::Target,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Mix,
+                NodeTypeLight::Switch,
+            ],
+            NodeType::Switch(..) => &[
+                NodeTypeLight::Graph,
+                NodeTypeLight::Flat,
+                NodeTypeLight::Linear,
+                NodeTypeLight::Target,
+                NodeTypeLight::Pid,
+                NodeTypeLight::Mix,
+            ],
+            _ => self.allowed_dep(),
         }
     }
 
@@ -322,22 +618,40 @@ impl NodeType {
             NodeType::Control(..) => NbInput::One,
             NodeType::Fan(..) => NbInput::Zero,
             NodeType::Temp(..) => NbInput::Zero,
+            NodeType::Metric(..) => NbInput::Zero,
             NodeType::CustomTemp(..) => NbInput::Infinity,
             NodeType::Graph(..) => NbInput::One,
             NodeType::Flat(..) => NbInput::Zero,
             NodeType::Linear(..) => NbInput::One,
             NodeType::Target(..) => NbInput::One,
+            NodeType::Pid(..) => NbInput::One,
+            NodeType::Filter(..) => NbInput::One,
+            NodeType::Delta(..) => NbInput::Two,
+            NodeType::Expression(..) => NbInput::Infinity,
+            NodeType::Mix(..) => NbInput::Infinity,
+            NodeType::Switch(..) => NbInput::Infinity,
+            NodeType::Schedule(..) => NbInput::Infinity,
         }
     }
 
     pub fn is_sensor(&self) -> bool {
-        matches!(self, NodeType::Fan(..) | NodeType::Temp(..))
+        matches!(
+            self,
+            NodeType::Fan(..) | NodeType::Temp(..) | NodeType::Metric(..)
+        )
     }
 
     pub fn is_behavior(&self) -> bool {
         matches!(
             self,
-            NodeType::Graph(..) | NodeType::Flat(..) | NodeType::Linear(..) | NodeType::Target(..)
+            NodeType::Graph(..)
+                | NodeType::Flat(..)
+                | NodeType::Linear(..)
+                | NodeType::Target(..)
+                | NodeType::Pid(..)
+                | NodeType::Mix(..)
+                | NodeType::Switch(..)
+                | NodeType::Schedule(..)
         )
     }
 
@@ -346,40 +660,30 @@ impl NodeType {
     }
 
     pub fn compare_update_priority(&self, other: &Self) -> Ordering {
+        self.update_rank().cmp(&other.update_rank())
+    }
+
+    /// Nodes with a lower rank are updated first. Each type only
+    /// depends on types with a lower rank, see `allowed_dep`, except
+    /// Mix and Switch which can use each other. Their inputs are
+    /// updated first anyway, and a cycle between them is reported
+    /// when the config is loaded.
+    fn update_rank(&self) -> u8 {
         match self {
-            NodeType::Control(_) => match other {
-                NodeType::Control(_) => Ordering::Equal,
-                _ => Ordering::Greater,
-            },
-            NodeType::Fan(_) => {
-                if other.is_sensor() {
-                    Ordering::Equal
-                } else {
-                    Ordering::Less
-                }
-            }
-            NodeType::Temp(_) => {
-                if other.is_sensor() {
-                    Ordering::Equal
-                } else {
-                    Ordering::Less
-                }
-            }
-            NodeType::CustomTemp(_) => match other {
-                NodeType::CustomTemp(_) => Ordering::Equal,
-                NodeType::Fan(_) => Ordering::Greater,
-                NodeType::Temp(_) => Ordering::Greater,
-                _ => Ordering::Less,
-            },
-            NodeType::Flat(_) => Ordering::Equal,
-
-            NodeType::Graph(_) | NodeType::Linear(..) | NodeType::Target(..) => match other {
-                NodeType::Control(_) => Ordering::Less,
-                NodeType::Fan(_) => Ordering::Greater,
-                NodeType::Temp(_) => Ordering::Greater,
-                NodeType::CustomTemp(_) => Ordering::Greater,
-                _ => Ordering::Equal,
-            },
+            NodeType::Fan(_) | NodeType::Temp(_) | NodeType::Metric(_) => 0,
+            NodeType::CustomTemp(_) => 1,
+            NodeType::Filter(_) => 2,
+            NodeType::Delta(_) => 3,
+            NodeType::Flat(_) => 4,
+            NodeType::Expression(_) => 5,
+            NodeType::Graph(_)
+            | NodeType::Linear(..)
+            | NodeType::Target(..)
+            | NodeType::Pid(..) => 6,
+            NodeType::Mix(_) => 7,
+            NodeType::Switch(_) => 8,
+            NodeType::Schedule(_) => 9,
+            NodeType::Control(_) => 10,
         }
     }
 }
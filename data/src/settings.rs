@@ -2,6 +2,11 @@ use light_enum::Values;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    auto_switch::AutoSwitch,
+    units::{SpeedUnit, TempUnit, Units},
+};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Settings {
     #[serde(default)]
@@ -12,6 +17,15 @@ pub struct Settings {
 
     #[serde(default)]
     pub current_config: Option<String>,
+
+    #[serde(default)]
+    pub temp_unit: TempUnit,
+
+    #[serde(default)]
+    pub speed_unit: SpeedUnit,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_switch: Option<AutoSwitch>,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default, Values)]
@@ -29,6 +43,9 @@ impl Default for Settings {
             theme: Default::default(),
             update_delay: default_update_delay(),
             current_config: Default::default(),
+            temp_unit: Default::default(),
+            speed_unit: Default::default(),
+            auto_switch: Default::default(),
         }
     }
 }
@@ -44,6 +61,13 @@ impl Settings {
             None => "",
         }
     }
+
+    pub fn units(&self) -> Units {
+        Units {
+            temp: self.temp_unit,
+            speed: self.speed_unit,
+        }
+    }
 }
 
 impl ToString for AppTheme {
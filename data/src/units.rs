@@ -0,0 +1,182 @@
+use hardware::Value;
+use light_enum::Values;
+use serde::{Deserialize, Serialize};
+
+use crate::node::ValueKind;
+
+/// Units used to display the values. The configs always store
+/// temps in °C and speeds in %, so they don't depend on the settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Units {
+    pub temp: TempUnit,
+    pub speed: SpeedUnit,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default, Values)]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default, Values)]
+pub enum SpeedUnit {
+    #[default]
+    Percent,
+    /// Raw PWM duty, from 0 to 255
+    Pwm,
+}
+
+impl TempUnit {
+    pub fn convert_celsius(self, celsius: f32) -> f32 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Fahrenheit => celsius * 1.8 + 32.0,
+        }
+    }
+
+    pub fn to_celsius(self, value: f32) -> f32 {
+        match self {
+            TempUnit::Celsius => value,
+            TempUnit::Fahrenheit => (value - 32.0) / 1.8,
+        }
+    }
+}
+
+impl SpeedUnit {
+    pub fn convert_percent(self, percent: f32) -> f32 {
+        match self {
+            SpeedUnit::Percent => percent,
+            SpeedUnit::Pwm => percent * 2.55,
+        }
+    }
+
+    pub fn to_percent(self, value: f32) -> f32 {
+        match self {
+            SpeedUnit::Percent => value,
+            SpeedUnit::Pwm => value / 2.55,
+        }
+    }
+}
+
+impl Units {
+    /// Value from the config (°C or %) to the displayed one.
+    pub fn display_value(&self, value: f32, kind: &ValueKind) -> f32 {
+        match kind {
+            ValueKind::Celsius => self.temp.convert_celsius(value),
+            ValueKind::TempDelta => {
+                self.temp.convert_celsius(value) - self.temp.convert_celsius(0.0)
+            }
+            ValueKind::Porcentage => self.speed.convert_percent(value),
+            ValueKind::Load | ValueKind::RPM | ValueKind::Unitless => value,
+        }
+    }
+
+    /// Displayed value to the one of the config (°C or %).
+    pub fn config_value(&self, value: f32, kind: &ValueKind) -> f32 {
+        match kind {
+            ValueKind::Celsius => self.temp.to_celsius(value),
+            ValueKind::TempDelta => self.temp.to_celsius(value) - self.temp.to_celsius(0.0),
+            ValueKind::Porcentage => self.speed.to_percent(value),
+            ValueKind::Load | ValueKind::RPM | ValueKind::Unitless => value,
+        }
+    }
+
+    /// Displayed value, rounded, without the unit
+    pub fn display_text(&self, value: f32, kind: &ValueKind) -> String {
+        (self.display_value(value, kind).round() as Value).to_string()
+    }
+
+    pub fn value_text(&self, value: Value, kind: &ValueKind) -> String {
+        let value = self.display_value(value as f32, kind).round() as Value;
+
+        match kind {
+            ValueKind::Celsius | ValueKind::TempDelta => match self.temp {
+                TempUnit::Celsius => fl!("value_celsius", value = value),
+                TempUnit::Fahrenheit => fl!("value_fahrenheit", value = value),
+            },
+            ValueKind::Porcentage => match self.speed {
+                SpeedUnit::Percent => fl!("value_percentage", value = value),
+                SpeedUnit::Pwm => fl!("value_pwm", value = value),
+            },
+            ValueKind::Load => fl!("value_percentage", value = value),
+            ValueKind::RPM => fl!("value_rpm", value = value),
+            ValueKind::Unitless => value.to_string(),
+        }
+    }
+
+    /// Symbol shown after an input field
+    pub fn symbol(&self, kind: &ValueKind) -> &'static str {
+        match kind {
+            ValueKind::Celsius | ValueKind::TempDelta => match self.temp {
+                TempUnit::Celsius => "°C",
+                TempUnit::Fahrenheit => "°F",
+            },
+            ValueKind::Porcentage => match self.speed {
+                SpeedUnit::Percent => "%",
+                SpeedUnit::Pwm => "PWM",
+            },
+            ValueKind::Load => "%",
+            ValueKind::RPM => "RPM",
+            ValueKind::Unitless => "",
+        }
+    }
+}
+
+impl ToString for TempUnit {
+    fn to_string(&self) -> String {
+        match self {
+            TempUnit::Celsius => fl!("celsius"),
+            TempUnit::Fahrenheit => fl!("fahrenheit"),
+        }
+    }
+}
+
+impl ToString for SpeedUnit {
+    fn to_string(&self) -> String {
+        match self {
+            SpeedUnit::Percent => fl!("percent"),
+            SpeedUnit::Pwm => fl!("pwm"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{node::ValueKind, utils::init_test_logging};
+
+    use super::{SpeedUnit, TempUnit, Units};
+
+    #[test]
+    fn test_conversions() {
+        init_test_logging();
+
+        let units = Units {
+            temp: TempUnit::Fahrenheit,
+            speed: SpeedUnit::Pwm,
+        };
+
+        assert!(units.display_value(100.0, &ValueKind::Celsius).round() == 212.0);
+        assert!(units.config_value(212.0, &ValueKind::Celsius).round() == 100.0);
+        assert!(units.display_value(100.0, &ValueKind::Porcentage).round() == 255.0);
+        assert!(units.config_value(255.0, &ValueKind::Porcentage).round() == 100.0);
+        assert!(units.display_value(1200.0, &ValueKind::RPM) == 1200.0);
+        assert!(units.display_value(50.0, &ValueKind::Load) == 50.0);
+        // a difference of temps doesn't have the offset
+        assert!(units.display_value(10.0, &ValueKind::TempDelta).round() == 18.0);
+        assert!(units.display_value(-10.0, &ValueKind::TempDelta).round() == -18.0);
+
+        // every displayed temp rounded to the config come back to itself
+        for celsius in 0..=255 {
+            let displayed = units
+                .display_value(celsius as f32, &ValueKind::Celsius)
+                .round();
+            let back = units.config_value(displayed, &ValueKind::Celsius).round();
+            assert!(back == celsius as f32);
+        }
+
+        let default = Units::default();
+        assert!(default.display_value(42.0, &ValueKind::Celsius) == 42.0);
+        assert!(default.symbol(&ValueKind::Porcentage) == "%");
+    }
+}
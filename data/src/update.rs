@@ -1,13 +1,19 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    time::Instant,
+};
 
-use hardware::{HardwareBridge, Mode, Value};
+use chrono::{Local, NaiveDateTime};
+use hardware::{HSensor, HardwareBridge, Mode, Value};
 
 use thiserror::Error;
 
 use crate::{
-    app_graph::{Nodes, RootNodes},
+    app_graph::{AppGraph, Nodes, RootNodes},
+    config::{emergency::Emergency, utils::expr::ExprError},
     id::Id,
-    node::{Node, NodeType},
+    node::{Input, Node, NodeState, NodeType},
 };
 
 #[derive(Error, Debug)]
@@ -18,8 +24,12 @@ pub enum UpdateError {
     ValueIsNone,
     #[error("Node {0} was invalid")]
     NodeIsInvalid(String),
+    #[error("Value {0} was rejected by the filter")]
+    RejectedValue(Value),
     #[error("No input data")]
     NoInputData,
+    #[error(transparent)]
+    Expression(#[from] ExprError),
     #[error("Can't set mode")]
     CantSetMode,
     #[error(transparent)]
@@ -28,7 +38,68 @@ pub enum UpdateError {
 
 type Result<T> = std::result::Result<T, UpdateError>;
 
-pub struct Update {}
+static EMERGENCY_VALUE: Value = 100;
+
+/// Source of the time given to the nodes, so tests can use a virtual one.
+pub trait Clock {
+    fn now(&self) -> Instant;
+    /// Used by the schedules
+    fn local_time(&self) -> NaiveDateTime;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn local_time(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// Time of an update, the same for all the nodes. Stateful nodes keep
+/// the time of their last update, so they compute the elapsed time
+/// even if they were not updated at each tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Tick {
+    pub now: Instant,
+    pub local_time: NaiveDateTime,
+}
+
+pub struct Update {
+    emergency_active: bool,
+    /// Controls set to their emergency value, given back on release
+    forced: HashSet<Id>,
+    /// Used by `optimized`, computed on the first update after a change of the graph
+    plan: Option<UpdatePlan>,
+    clock: Box<dyn Clock>,
+}
+
+/// Nodes needed by the root nodes, sorted so that a node come after its
+/// inputs. The order and the validity of the nodes can only change with
+/// the graph, so they are computed once instead of at each update.
+#[derive(Debug, Default)]
+struct UpdatePlan {
+    /// Generation of the graph the plan was computed from
+    generation: u64,
+    steps: Vec<Step>,
+    /// Last value of each step, reused between updates
+    values: Vec<Option<Value>>,
+    /// Buffer of the input values of the step being updated
+    input_values: Vec<Value>,
+}
+
+#[derive(Debug)]
+struct Step {
+    id: Id,
+    /// Index of the inputs in `UpdatePlan::steps`
+    inputs: Vec<usize>,
+    /// The node and all its inputs are valid
+    is_valid: bool,
+}
 
 impl Default for Update {
     fn default() -> Self {
@@ -38,34 +109,103 @@ impl Default for Update {
 
 impl Update {
     pub fn new() -> Self {
-        Self {}
+        Self::with_clock(SystemClock)
+    }
+
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self {
+            emergency_active: false,
+            forced: HashSet::new(),
+            plan: None,
+            clock: Box::new(clock),
+        }
+    }
+
+    fn tick(&self) -> Tick {
+        Tick {
+            now: self.clock.now(),
+            local_time: self.clock.local_time(),
+        }
+    }
+
+    /// Must be called when the graph is replaced, so `optimized` compute
+    /// the order of the nodes again. Changes made through `AppGraph` are
+    /// detected with its generation.
+    pub fn invalidate_plan(&mut self) {
+        self.plan = None;
     }
 
-    // todo: remember what nodes are valid
     /// Update graph in an optimal way. This shouln't be use
     /// with a graphical interface.
     /// Warning: doesn't call update from the bridge, it's the role of the caller.
     pub fn optimized<H: HardwareBridge>(
         &mut self,
-        nodes: &mut Nodes,
-        root_nodes: &RootNodes,
+        app_graph: &mut AppGraph,
         bridge: &mut H,
     ) -> Result<()> {
-        let mut updated: HashSet<Id> = HashSet::new();
-        for node_id in root_nodes {
-            if let Err(e) = Self::update_rec(nodes, node_id, &mut updated, bridge) {
-                error!("Can't update node: {}.", e);
-            }
+        let tick = self.tick();
+        let generation = app_graph.generation();
+        let AppGraph {
+            nodes,
+            root_nodes,
+            emergency,
+            ..
+        } = app_graph;
+
+        let emergency_active = self.update_emergency(emergency.as_ref(), bridge);
+
+        if !emergency_active {
+            self.release_emergency(nodes, bridge);
         }
-        Ok(())
+
+        if self
+            .plan
+            .as_ref()
+            .is_some_and(|plan| plan.generation != generation)
+        {
+            debug!("The graph changed, the update plan is computed again.");
+            self.plan = None;
+        }
+
+        let plan = self.plan.get_or_insert_with(|| UpdatePlan {
+            generation,
+            ..UpdatePlan::new(nodes, root_nodes)
+        });
+
+        // the other nodes keep their state up to date during an emergency
+        let res = plan.run(nodes, &tick, emergency_active, bridge);
+        if res.is_err() {
+            // the graph changed without invalidating the plan
+            self.plan = None;
+        }
+
+        if emergency_active {
+            self.set_root_nodes_to_emergency(nodes, tick.now, bridge);
+        }
+        res
     }
 
     /// Doesn't update root nodes and doesn't re update nodes that could have been updated (fans).
     /// Warning: doesn't call update from the bridge, it's the role of the caller.
-    pub fn all<H: HardwareBridge>(&mut self, nodes: &mut Nodes, bridge: &mut H) -> Result<()> {
+    pub fn all<H: HardwareBridge>(
+        &mut self,
+        nodes: &mut Nodes,
+        emergency: Option<&Emergency>,
+        bridge: &mut H,
+    ) -> Result<()> {
+        let tick = self.tick();
+        let emergency_active = self.update_emergency(emergency, bridge);
+
+        if !emergency_active {
+            self.release_emergency(nodes, bridge);
+        }
+
         let ids_to_update_sorted: Vec<Id>;
         {
-            let mut key_values = nodes.iter().collect::<Vec<_>>();
+            let mut key_values = nodes
+                .iter()
+                .filter(|(_, node)| !(emergency_active && node.is_root()))
+                .collect::<Vec<_>>();
 
             key_values.sort_by(|(_, first), (_, other)| {
                 first.node_type.compare_update_priority(&other.node_type)
@@ -76,23 +216,145 @@ impl Update {
 
         let mut updated = HashSet::new();
         for id in ids_to_update_sorted {
-            if let Err(e) = Self::update_rec(nodes, &id, &mut updated, bridge) {
+            if let Err(e) = Self::update_rec(nodes, &id, &mut updated, &tick, bridge) {
                 error!("can't update node: {}", e);
             }
         }
 
+        if emergency_active {
+            self.set_root_nodes_to_emergency(nodes, tick.now, bridge);
+        }
+
         Ok(())
     }
 
+    /// Read the watched temps directly from the bridge, so a misconfigured
+    /// graph can't prevent the emergency mode. Return true if it is active.
+    fn update_emergency<H: HardwareBridge>(
+        &mut self,
+        emergency: Option<&Emergency>,
+        bridge: &mut H,
+    ) -> bool {
+        let Some(emergency) = emergency else {
+            self.emergency_active = false;
+            return false;
+        };
+
+        let temps: Vec<Rc<HSensor>> = bridge
+            .hardware()
+            .temps
+            .iter()
+            .filter(|temp_h| emergency.watch(temp_h))
+            .cloned()
+            .collect();
+
+        let mut triggered_by = None;
+        let mut released = true;
+
+        for temp_h in temps {
+            match bridge.get_sensor_value(&temp_h) {
+                Ok(value) => {
+                    if emergency.is_triggered_by(value, temp_h.crit) && triggered_by.is_none() {
+                        triggered_by = Some((temp_h.name.clone(), value));
+                    }
+                    released &= emergency.is_released_by(value, temp_h.crit);
+                }
+                Err(e) => {
+                    // we can't know if this temp is safe
+                    released = false;
+                    error!(
+                        "Can't read temp {} for the emergency mode: {}.",
+                        temp_h.name, e
+                    );
+                }
+            }
+        }
+
+        if !self.emergency_active {
+            if let Some((name, value)) = triggered_by {
+                warn!("Emergency mode activated: {} is at {}.", name, value);
+                self.emergency_active = true;
+            }
+        } else if released {
+            info!("Emergency mode deactivated.");
+            self.emergency_active = false;
+        }
+
+        self.emergency_active
+    }
+
+    /// Set all active controls to their emergency value, whatever their inputs.
+    fn set_root_nodes_to_emergency<H: HardwareBridge>(
+        &mut self,
+        nodes: &mut Nodes,
+        now: Instant,
+        bridge: &mut H,
+    ) {
+        for node in nodes.values_mut() {
+            let (NodeType::Control(control), NodeState::Control(state)) =
+                (&mut node.node_type, &mut node.state)
+            else {
+                continue;
+            };
+
+            if !control.active || control.control_h.is_none() {
+                continue;
+            }
+
+            let emergency_value = control.emergency_value.map_or(EMERGENCY_VALUE, Value::from);
+
+            // the ramp will start from the emergency value once released
+            state.reset_output(emergency_value, now);
+
+            if self.forced.contains(&node.id) && control.mode_set == Some(Mode::Manual) {
+                continue;
+            }
+
+            // the behavior must write its value again once released
+            state.written = None;
+
+            match control.set_value_to_all(emergency_value, bridge) {
+                Ok(value) => {
+                    node.value = Some(value);
+                    self.forced.insert(node.id);
+                }
+                Err(e) => {
+                    error!(
+                        "Can't set control {} to emergency value: {}.",
+                        control.name, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Give the forced controls back to their behavior. The invalid
+    /// ones are not updated anymore, so the hardware take them back.
+    fn release_emergency<H: HardwareBridge>(&mut self, nodes: &mut Nodes, bridge: &mut H) {
+        for node_id in std::mem::take(&mut self.forced) {
+            if !nodes.contains_key(&node_id) || Self::validate_rec(nodes, &node_id) {
+                continue;
+            }
+
+            if let Err(e) = self.set_node_to_auto(nodes, &node_id, bridge) {
+                error!("Can't set control to auto after an emergency: {}.", e);
+            }
+        }
+    }
+
     pub fn nodes_which_update_can_change<H: HardwareBridge>(
         &mut self,
         nodes: &mut Nodes,
         bridge: &mut H,
     ) -> Result<()> {
+        let now = self.clock.now();
+
         for node in nodes.values_mut() {
-            let value = match &mut node.node_type {
-                crate::node::NodeType::Control(control) => Some(control.get_value(bridge)),
-                crate::node::NodeType::Fan(fan) => Some(fan.get_value(bridge)),
+            let value = match (&mut node.node_type, &mut node.state) {
+                (NodeType::Control(control), _) => Some(control.get_value(bridge)),
+                (NodeType::Fan(fan), NodeState::Fan(state)) => {
+                    Some(fan.get_value(state, bridge, now))
+                }
                 _ => None,
             };
 
@@ -193,6 +455,7 @@ impl Update {
         nodes: &mut Nodes,
         node_id: &Id,
         updated: &mut HashSet<Id>,
+        tick: &Tick,
         bridge: &mut H,
     ) -> Result<Option<Value>> {
         if updated.contains(node_id) {
@@ -220,7 +483,7 @@ impl Update {
 
         let mut input_values = Vec::new();
         for id in &input_ids {
-            match Self::update_rec(nodes, id, updated, bridge)? {
+            match Self::update_rec(nodes, id, updated, tick, bridge)? {
                 Some(value) => input_values.push(value),
                 None => {
                     return match nodes.get_mut(node_id) {
@@ -240,32 +503,206 @@ impl Update {
             return Err(UpdateError::NodeNotFound(*node_id));
         };
 
-        node.update(&input_values, bridge)?;
+        node.update(&input_values, tick, bridge)?;
 
         Ok(node.value)
     }
 }
 
+impl UpdatePlan {
+    fn new(nodes: &Nodes, root_nodes: &RootNodes) -> Self {
+        let mut plan = Self::default();
+        let mut indexes = HashMap::new();
+        let mut visiting = HashSet::new();
+
+        for node_id in root_nodes {
+            plan.add_rec(nodes, node_id, &mut indexes, &mut visiting);
+        }
+
+        plan.values = vec![None; plan.steps.len()];
+        plan
+    }
+
+    /// Add the inputs of the node, then the node.
+    /// Return its index, or None if it can't be used.
+    fn add_rec(
+        &mut self,
+        nodes: &Nodes,
+        node_id: &Id,
+        indexes: &mut HashMap<Id, usize>,
+        visiting: &mut HashSet<Id>,
+    ) -> Option<usize> {
+        if let Some(index) = indexes.get(node_id) {
+            return Some(*index);
+        }
+
+        let Some(node) = nodes.get(node_id) else {
+            error!("Node id \"{}\" was not found.", node_id);
+            return None;
+        };
+
+        if !visiting.insert(*node_id) {
+            error!("Node {} depends on itself.", node.name());
+            return None;
+        }
+
+        let mut is_valid = node.node_type.is_valid();
+        let mut inputs = Vec::with_capacity(node.inputs.len());
+
+        // the inputs of an invalid node are useless
+        if is_valid {
+            for input in &node.inputs {
+                match self.add_rec(nodes, &input.id, indexes, visiting) {
+                    Some(index) => {
+                        is_valid &= self.steps[index].is_valid;
+                        inputs.push(index);
+                    }
+                    None => is_valid = false,
+                }
+            }
+        }
+
+        visiting.remove(node_id);
+
+        let index = self.steps.len();
+        self.steps.push(Step {
+            id: *node_id,
+            inputs,
+            is_valid,
+        });
+        indexes.insert(*node_id, index);
+        Some(index)
+    }
+
+    /// The root nodes are skipped during an emergency, `Update` write them.
+    fn run<H: HardwareBridge>(
+        &mut self,
+        nodes: &mut Nodes,
+        tick: &Tick,
+        emergency_active: bool,
+        bridge: &mut H,
+    ) -> Result<()> {
+        let UpdatePlan {
+            steps,
+            values,
+            input_values,
+            ..
+        } = self;
+
+        for (index, step) in steps.iter().enumerate() {
+            let Some(node) = nodes.get_mut(&step.id) else {
+                return Err(UpdateError::NodeNotFound(step.id));
+            };
+
+            if emergency_active && node.is_root() {
+                values[index] = None;
+                continue;
+            }
+
+            input_values.clear();
+            input_values.extend(step.inputs.iter().map_while(|input| values[*input]));
+
+            values[index] = if step.is_valid && input_values.len() == step.inputs.len() {
+                if let Err(e) = node.update(input_values, tick, bridge) {
+                    error!("Can't update node {}: {}.", node.name(), e);
+                }
+                node.value
+            } else {
+                if !node.is_root() {
+                    node.value = None;
+                }
+                None
+            };
+        }
+        Ok(())
+    }
+}
+
+/// One value per slot, in the order of `names`. `input_values` follow
+/// `inputs`, where a node used in several slots is only present once.
+fn slot_values<'a>(
+    names: impl IntoIterator<Item = &'a String>,
+    inputs: &[Input],
+    input_values: &[Value],
+) -> Vec<Value> {
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let index = inputs.iter().position(|input| &input.name == name)?;
+            input_values.get(index).copied()
+        })
+        .collect()
+}
+
 impl Node {
-    fn update<H: HardwareBridge>(&mut self, input_values: &[Value], bridge: &mut H) -> Result<()> {
-        let value = match &mut self.node_type {
-            crate::node::NodeType::Control(control) => {
-                let input_value = input_values[0];
-                return if self.value == Some(input_value) {
+    fn update<H: HardwareBridge>(
+        &mut self,
+        input_values: &[Value],
+        tick: &Tick,
+        bridge: &mut H,
+    ) -> Result<()> {
+        let value = match (&mut self.node_type, &mut self.state) {
+            (NodeType::Control(control), NodeState::Control(state)) => {
+                let input_value = control.output(state, input_values[0], tick.now);
+                // in closed loop, the duty change even if the value doesn't
+                return if state.written == Some(input_value)
+                    && control.mode_set == Some(Mode::Manual)
+                    && control.rpm.is_none()
+                {
                     debug!("Control {} already set to {}", control.name, input_value);
                     Ok(())
                 } else {
                     debug!("Before setting control {} to {}", control.name, input_value);
-                    control.set_value(input_value, bridge).map(|_| ())
+                    let res = control.apply(state, input_value, tick.now, bridge);
+                    state.written = res.as_ref().ok().map(|_| input_value);
+                    res.map(|_| ())
                 };
             }
-            crate::node::NodeType::Fan(fan) => fan.get_value(bridge),
-            crate::node::NodeType::Temp(temp) => temp.get_value(bridge),
-            crate::node::NodeType::CustomTemp(custom_temp) => custom_temp.get_value(input_values),
-            crate::node::NodeType::Graph(graph) => graph.get_value(input_values[0]),
-            crate::node::NodeType::Flat(flat) => Ok(flat.value.into()),
-            crate::node::NodeType::Linear(linear, ..) => linear.get_value(input_values[0]),
-            crate::node::NodeType::Target(target, ..) => target.get_value(input_values[0]),
+            (NodeType::Fan(fan), NodeState::Fan(state)) => fan.get_value(state, bridge, tick.now),
+            (NodeType::Temp(temp), NodeState::Temp(state)) => {
+                temp.get_value(state, bridge, tick.now)
+            }
+            (NodeType::Metric(metric), _) => metric.get_value(bridge),
+            (NodeType::CustomTemp(custom_temp), _) => custom_temp.get_value(&slot_values(
+                &custom_temp.inputs,
+                &self.inputs,
+                input_values,
+            )),
+            (NodeType::Graph(graph), NodeState::Graph(state)) => {
+                graph.get_value(state, input_values[0])
+            }
+            (NodeType::Flat(flat), _) => Ok(flat.value.into()),
+            (NodeType::Linear(linear), NodeState::Linear(state)) => {
+                linear.get_value(state, input_values[0])
+            }
+            (NodeType::Target(target), NodeState::Target(state)) => {
+                target.get_value(state, input_values[0])
+            }
+            (NodeType::Pid(pid), NodeState::Pid(state)) => {
+                pid.get_value(state, input_values[0], tick.now)
+            }
+            (NodeType::Filter(filter), NodeState::Filter(state)) => {
+                filter.get_value(state, input_values[0], tick.now)
+            }
+            (NodeType::Delta(delta), _) => delta.get_value(&slot_values(
+                [&delta.input, &delta.reference].into_iter().flatten(),
+                &self.inputs,
+                input_values,
+            )),
+            (NodeType::Mix(mix), _) => {
+                mix.get_value(&slot_values(&mix.inputs, &self.inputs, input_values))
+            }
+            (NodeType::Switch(switch), NodeState::Switch(state)) => {
+                switch.get_value(state, &self.inputs, input_values)
+            }
+            (NodeType::Schedule(schedule), _) => {
+                schedule.get_value(&self.inputs, input_values, tick.local_time)
+            }
+            (NodeType::Expression(expression), _) => {
+                expression.get_value(&self.inputs, input_values)
+            }
+            // the state is created with the node, from its type
+            (node_type, _) => Err(UpdateError::NodeIsInvalid(node_type.name().clone())),
         };
 
         match value {
@@ -280,3 +717,421 @@ impl Node {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use chrono::{NaiveDate, NaiveDateTime};
+    use hardware::{
+        fake_hardware::FakeHardwareBridge, HControl, HSensor, Hardware, HardwareBridge,
+        HardwareError, Mode, Value,
+    };
+
+    use crate::{
+        app_graph::{AppGraph, Nodes},
+        config::{
+            control::Control,
+            delta::Delta,
+            emergency::Emergency,
+            filter::{Filter, FilterKind},
+            flat::Flat,
+            graph::Graph,
+            mix::{Mix, MixKind},
+            schedule::{Period, Schedule, TimeOfDay},
+            temp::Temp,
+            Config,
+        },
+        id::IdGenerator,
+        node::{Node, ToNode},
+        utils::init_test_logging,
+    };
+
+    use super::{Clock, Tick, Update, UpdatePlan};
+
+    struct VirtualClock {
+        start: Instant,
+        elapsed: Rc<Cell<Duration>>,
+        local_time: NaiveDateTime,
+    }
+
+    impl Clock for VirtualClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn local_time(&self) -> NaiveDateTime {
+            self.local_time + self.elapsed.get()
+        }
+    }
+
+    /// The nodes used by the tests don't read the hardware.
+    struct NoHardware(Hardware);
+
+    impl HardwareBridge for NoHardware {
+        fn new() -> Result<Self, HardwareError> {
+            Ok(Self(Hardware::default()))
+        }
+
+        fn hardware(&self) -> &Hardware {
+            &self.0
+        }
+
+        fn get_sensor_value(&mut self, sensor: &HSensor) -> Result<Value, HardwareError> {
+            Err(HardwareError::Unsupported(sensor.name.clone()))
+        }
+
+        fn get_control_value(&mut self, control: &HControl) -> Result<Value, HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+
+        fn set_value(&mut self, control: &HControl, _value: Value) -> Result<(), HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+
+        fn set_mode(&mut self, control: &HControl, _mode: &Mode) -> Result<(), HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+    }
+
+    /// The temps of the fake hardware, all at `temp`, and what is
+    /// written to its controls.
+    struct RecordingBridge {
+        fake: FakeHardwareBridge,
+        temp: Value,
+        writes: Vec<(String, Value)>,
+        modes: Vec<(String, Mode)>,
+    }
+
+    impl HardwareBridge for RecordingBridge {
+        fn new() -> Result<Self, HardwareError> {
+            Ok(Self {
+                fake: FakeHardwareBridge::new()?,
+                temp: 0,
+                writes: Vec::new(),
+                modes: Vec::new(),
+            })
+        }
+
+        fn hardware(&self) -> &Hardware {
+            self.fake.hardware()
+        }
+
+        fn get_sensor_value(&mut self, _sensor: &HSensor) -> Result<Value, HardwareError> {
+            Ok(self.temp)
+        }
+
+        fn get_control_value(&mut self, control: &HControl) -> Result<Value, HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+
+        fn set_value(&mut self, control: &HControl, value: Value) -> Result<(), HardwareError> {
+            self.writes.push((control.name.clone(), value));
+            Ok(())
+        }
+
+        fn set_mode(&mut self, control: &HControl, mode: &Mode) -> Result<(), HardwareError> {
+            self.modes.push((control.name.clone(), mode.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        init_test_logging();
+
+        let mut id_generator = IdGenerator::new();
+        let mut nodes = Nodes::new();
+        let hardware = Hardware::default();
+
+        let insert = |node: Node, nodes: &mut Nodes| {
+            let id = node.id;
+            nodes.insert(id, node);
+            id
+        };
+
+        let flat = Flat {
+            name: "flat".into(),
+            value: 40,
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        let flat = insert(flat, &mut nodes);
+
+        // without input
+        let graph = Graph {
+            name: "graph".into(),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        let graph = insert(graph, &mut nodes);
+
+        let mix = |name: &str, inputs: &[&str], nodes: &Nodes, id_generator: &mut IdGenerator| {
+            Mix {
+                name: name.into(),
+                inputs: inputs.iter().map(|i| i.to_string()).collect(),
+                ..Default::default()
+            }
+            .to_node(id_generator, nodes, &hardware)
+        };
+
+        let valid = mix("valid", &["flat"], &nodes, &mut id_generator);
+        let valid = insert(valid, &mut nodes);
+        let invalid = mix("invalid", &["flat", "graph"], &nodes, &mut id_generator);
+        let invalid = insert(invalid, &mut nodes);
+        let last = Schedule {
+            name: "last".into(),
+            input: Some("valid".into()),
+            ..Default::default()
+        }
+        .to_node(&mut id_generator, &nodes, &hardware);
+        let last = insert(last, &mut nodes);
+
+        let plan = UpdatePlan::new(&nodes, &vec![last, invalid]);
+
+        let order = plan.steps.iter().map(|step| step.id).collect::<Vec<_>>();
+        assert!(order == vec![flat, valid, last, graph, invalid]);
+
+        let validity = plan
+            .steps
+            .iter()
+            .map(|step| step.is_valid)
+            .collect::<Vec<_>>();
+        assert!(validity == vec![true, true, true, false, false]);
+
+        // flat is only added once
+        assert!(plan.steps[2].inputs == vec![1]);
+        assert!(plan.steps[4].inputs == vec![0, 3]);
+        assert!(plan.values.len() == plan.steps.len());
+    }
+
+    #[test]
+    fn test_virtual_clock() {
+        init_test_logging();
+
+        let mut bridge = NoHardware::new().unwrap();
+        let start = Instant::now();
+        let elapsed = Rc::new(Cell::new(Duration::ZERO));
+        let local_time = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(21, 59, 0)
+            .unwrap();
+
+        let flat = |name: &str, value| Flat {
+            name: name.into(),
+            value,
+        };
+
+        let config = Config {
+            flats: vec![flat("normal", 70), flat("quiet", 30)],
+            schedules: vec![Schedule {
+                name: "schedule".into(),
+                input: Some("normal".into()),
+                periods: vec![Period {
+                    start: TimeOfDay {
+                        hour: 22,
+                        minute: 0,
+                    },
+                    end: TimeOfDay { hour: 7, minute: 0 },
+                    input: Some("quiet".into()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app_graph = AppGraph::from_config(config, bridge.hardware());
+
+        let mut update = Update::with_clock(VirtualClock {
+            start,
+            elapsed: elapsed.clone(),
+            local_time,
+        });
+
+        let schedule = |app_graph: &AppGraph| {
+            app_graph
+                .nodes
+                .values()
+                .find(|node| node.name() == "schedule")
+                .and_then(|node| node.value)
+        };
+
+        update.all(&mut app_graph.nodes, None, &mut bridge).unwrap();
+        assert!(schedule(&app_graph) == Some(70));
+
+        elapsed.set(Duration::from_secs(60));
+        update.all(&mut app_graph.nodes, None, &mut bridge).unwrap();
+        assert!(schedule(&app_graph) == Some(30));
+
+        // the state of the filter follow the time of the ticks
+        let mut node = Filter {
+            name: "filter".into(),
+            kind: FilterKind::Ema,
+            span: 2,
+            input: None,
+        }
+        .to_node(&mut IdGenerator::new(), &Nodes::new(), bridge.hardware());
+
+        let tick = |secs| Tick {
+            now: start + Duration::from_secs(secs),
+            local_time,
+        };
+
+        node.update(&[40], &tick(0), &mut bridge).unwrap();
+        assert!(node.value == Some(40));
+        node.update(&[140], &tick(2), &mut bridge).unwrap();
+        assert!(node.value == Some(103));
+    }
+
+    #[test]
+    fn test_repeated_inputs() {
+        init_test_logging();
+
+        let mut bridge = NoHardware::new().unwrap();
+        let mut id_generator = IdGenerator::new();
+        let mut nodes = Nodes::new();
+        let tick = Update::new().tick();
+
+        let mut insert = |node: Node| {
+            nodes.insert(node.id, node);
+        };
+
+        insert(
+            Temp {
+                name: "cpu".into(),
+                ..Default::default()
+            }
+            .to_node(&mut id_generator, &Nodes::new(), bridge.hardware()),
+        );
+        for (name, value) in [("quiet", 40), ("loud", 70)] {
+            insert(
+                Flat {
+                    name: name.into(),
+                    value,
+                }
+                .to_node(&mut id_generator, &Nodes::new(), bridge.hardware()),
+            );
+        }
+
+        // the same temp in both slots
+        let mut delta = Delta {
+            name: "delta".into(),
+            input: Some("cpu".into()),
+            reference: Some("cpu".into()),
+        }
+        .to_node(&mut id_generator, &nodes, bridge.hardware());
+        assert!(delta.inputs.len() == 1);
+
+        delta.update(&[55], &tick, &mut bridge).unwrap();
+        assert!(delta.value == Some(0));
+
+        let mut mix = Mix {
+            name: "mix".into(),
+            kind: MixKind::Average,
+            inputs: vec!["quiet".into(), "quiet".into(), "loud".into()],
+        }
+        .to_node(&mut id_generator, &nodes, bridge.hardware());
+        assert!(mix.inputs.len() == 2);
+
+        mix.update(&[40, 70], &tick, &mut bridge).unwrap();
+        assert!(mix.value == Some(50));
+    }
+
+    #[test]
+    fn test_emergency() {
+        init_test_logging();
+
+        let mut bridge = RecordingBridge::new().unwrap();
+
+        let config = Config {
+            flats: vec![Flat {
+                name: "flat".into(),
+                value: 40,
+            }],
+            controls: vec![
+                Control::new(
+                    "valid".into(),
+                    Some("control1".into()),
+                    Some("flat".into()),
+                    true,
+                    None,
+                ),
+                // without input
+                Control::new("invalid".into(), Some("control2".into()), None, true, None),
+            ],
+            ..Default::default()
+        };
+        let mut app_graph = AppGraph::from_config(config, bridge.hardware());
+        app_graph.emergency = Some(Emergency {
+            trigger_temp: 90,
+            release_temp: 80,
+            ..Default::default()
+        });
+
+        let mut update = Update::new();
+        let mut run = |temp, bridge: &mut RecordingBridge| {
+            bridge.temp = temp;
+            bridge.writes.clear();
+            update.optimized(&mut app_graph, bridge).unwrap();
+            std::mem::take(&mut bridge.writes)
+        };
+        let writes = |writes: &[(&str, Value)]| {
+            writes
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(run(50, &mut bridge) == writes(&[("control1", 40)]));
+        assert!(run(50, &mut bridge).is_empty());
+
+        for _ in 0..2 {
+            let emergency_writes = run(95, &mut bridge);
+            assert!(emergency_writes.len() == 2);
+            assert!(emergency_writes.iter().all(|(_, value)| *value == 100));
+            assert!(run(95, &mut bridge).is_empty());
+
+            bridge.modes.clear();
+            // the valid control follow its behavior again
+            assert!(run(70, &mut bridge) == writes(&[("control1", 40)]));
+            assert!(bridge.modes == vec![("control2".to_string(), Mode::Auto)]);
+        }
+    }
+
+    #[test]
+    fn test_plan_generation() {
+        init_test_logging();
+
+        let mut bridge = RecordingBridge::new().unwrap();
+
+        let config = Config {
+            flats: vec![Flat {
+                name: "flat".into(),
+                value: 40,
+            }],
+            controls: vec![Control::new(
+                "control".into(),
+                Some("control1".into()),
+                Some("flat".into()),
+                true,
+                None,
+            )],
+            ..Default::default()
+        };
+        let mut app_graph = AppGraph::from_config(config, bridge.hardware());
+        let mut update = Update::new();
+
+        update.optimized(&mut app_graph, &mut bridge).unwrap();
+        assert!(update.plan.as_ref().unwrap().steps.len() == 2);
+
+        // the plan must not keep the removed node
+        let control = app_graph.root_nodes[0];
+        app_graph.remove_node(control);
+
+        update.optimized(&mut app_graph, &mut bridge).unwrap();
+        assert!(update.plan.as_ref().unwrap().steps.is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{HControl, HSensor, Hardware, HardwareBridge, Mode, Value};
+
+/// What would have been written last to a control during a dry run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunWrite {
+    /// Duty, in %
+    Value(Value),
+    /// Speed the driver would regulate to, in RPM
+    RpmTarget(Value),
+}
+
+/// Wrap a bridge so the graph can be evaluated without touching the hardware.
+/// Values and modes that would have been written are logged and kept, so
+/// the UI can display them, and the last value of a control is returned
+/// when reading it.
+pub struct DryRunBridge<H: HardwareBridge> {
+    bridge: H,
+    written: HashMap<String, DryRunWrite>,
+}
+
+impl<H: HardwareBridge> DryRunBridge<H> {
+    pub fn wrap(bridge: H) -> Self {
+        Self {
+            bridge,
+            written: HashMap::new(),
+        }
+    }
+}
+
+impl<H: HardwareBridge> HardwareBridge for DryRunBridge<H> {
+    const TIME_TO_UPDATE: Duration = H::TIME_TO_UPDATE;
+
+    fn new() -> crate::Result<Self> {
+        H::new().map(Self::wrap)
+    }
+
+    fn hardware(&self) -> &Hardware {
+        self.bridge.hardware()
+    }
+
+    fn get_sensor_value(&mut self, sensor: &HSensor) -> crate::Result<Value> {
+        self.bridge.get_sensor_value(sensor)
+    }
+
+    fn get_metric_value(&mut self, metric: &HSensor) -> crate::Result<Value> {
+        self.bridge.get_metric_value(metric)
+    }
+
+    fn get_control_value(&mut self, control: &HControl) -> crate::Result<Value> {
+        match self.written.get(&control.hardware_id) {
+            Some(DryRunWrite::Value(value)) => Ok(*value),
+            // the duty is chosen by the driver, the real one is the closest
+            Some(DryRunWrite::RpmTarget(_)) | None => self.bridge.get_control_value(control),
+        }
+    }
+
+    fn set_value(&mut self, control: &HControl, value: Value) -> crate::Result<()> {
+        info!("Dry run: {} would be set to {}.", control.name, value);
+        self.written
+            .insert(control.hardware_id.clone(), DryRunWrite::Value(value));
+        Ok(())
+    }
+
+    fn set_mode(&mut self, control: &HControl, mode: &Mode) -> crate::Result<()> {
+        info!(
+            "Dry run: mode of {} would be set to {}.",
+            control.name, mode
+        );
+        match mode {
+            Mode::Manual => {
+                if let Some(DryRunWrite::RpmTarget(_)) = self.written.get(&control.hardware_id) {
+                    self.written.remove(&control.hardware_id);
+                }
+            }
+            Mode::Rpm => {
+                if let Some(DryRunWrite::Value(_)) = self.written.get(&control.hardware_id) {
+                    self.written.remove(&control.hardware_id);
+                }
+            }
+            // the hardware would be in charge again
+            Mode::Auto | Mode::Specific(_) => {
+                self.written.remove(&control.hardware_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_rpm_target(&mut self, control: &HControl, rpm: Value) -> crate::Result<()> {
+        info!(
+            "Dry run: target of {} would be set to {} RPM.",
+            control.name, rpm
+        );
+        self.written
+            .insert(control.hardware_id.clone(), DryRunWrite::RpmTarget(rpm));
+        Ok(())
+    }
+
+    fn update(&mut self) -> crate::Result<()> {
+        self.bridge.update()
+    }
+
+    fn shutdown(&mut self) -> crate::Result<()> {
+        self.bridge.shutdown()
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+
+    fn dry_run_write(&self, control: &HControl) -> Option<DryRunWrite> {
+        self.written.get(&control.hardware_id).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::{DryRunBridge, DryRunWrite};
+    use crate::{HControl, HSensor, Hardware, HardwareBridge, HardwareError, Mode, Value};
+
+    fn init_test_logging() {
+        let _ = env_logger::builder().format_timestamp(None).try_init();
+    }
+
+    /// Always read 42 and fail on any write, so the tests catch
+    /// values that reach the hardware.
+    struct ReadOnlyBridge {
+        hardware: Hardware,
+    }
+
+    impl HardwareBridge for ReadOnlyBridge {
+        fn new() -> Result<Self, HardwareError> {
+            let control = HControl {
+                name: "Control 1".into(),
+                hardware_id: "control1".into(),
+                info: String::new(),
+                rpm_target: true,
+                internal_index: 0,
+            };
+            Ok(Self {
+                hardware: Hardware {
+                    controls: vec![Rc::new(control)],
+                    ..Default::default()
+                },
+            })
+        }
+
+        fn hardware(&self) -> &Hardware {
+            &self.hardware
+        }
+
+        fn get_sensor_value(&mut self, sensor: &HSensor) -> Result<Value, HardwareError> {
+            Err(HardwareError::Unsupported(sensor.name.clone()))
+        }
+
+        fn get_control_value(&mut self, _control: &HControl) -> Result<Value, HardwareError> {
+            Ok(42)
+        }
+
+        fn set_value(&mut self, control: &HControl, _value: Value) -> Result<(), HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+
+        fn set_mode(&mut self, control: &HControl, _mode: &Mode) -> Result<(), HardwareError> {
+            Err(HardwareError::Unsupported(control.name.clone()))
+        }
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        init_test_logging();
+
+        let mut bridge = DryRunBridge::<ReadOnlyBridge>::new().unwrap();
+        let control = bridge.hardware().controls[0].clone();
+
+        assert!(bridge.is_dry_run());
+        assert!(bridge.get_control_value(&control).unwrap() == 42);
+        assert!(bridge.dry_run_write(&control).is_none());
+
+        bridge.set_mode(&control, &Mode::Manual).unwrap();
+        bridge.set_value(&control, 70).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 70);
+        assert!(bridge.dry_run_write(&control) == Some(DryRunWrite::Value(70)));
+
+        bridge.set_value(&control, 30).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 30);
+    }
+
+    #[test]
+    fn test_mode_clears_value() {
+        init_test_logging();
+
+        let mut bridge = DryRunBridge::<ReadOnlyBridge>::new().unwrap();
+        let control = bridge.hardware().controls[0].clone();
+
+        bridge.set_value(&control, 70).unwrap();
+        bridge.set_mode(&control, &Mode::Manual).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 70);
+
+        bridge.set_mode(&control, &Mode::Auto).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 42);
+        assert!(bridge.dry_run_write(&control).is_none());
+
+        bridge.set_value(&control, 70).unwrap();
+        bridge.set_mode(&control, &Mode::Specific(5)).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 42);
+    }
+
+    #[test]
+    fn test_rpm_target() {
+        init_test_logging();
+
+        let mut bridge = DryRunBridge::<ReadOnlyBridge>::new().unwrap();
+        let control = bridge.hardware().controls[0].clone();
+
+        bridge.set_value(&control, 70).unwrap();
+        bridge.set_mode(&control, &Mode::Rpm).unwrap();
+        // the duty written before is stale now
+        assert!(bridge.get_control_value(&control).unwrap() == 42);
+
+        bridge.set_rpm_target(&control, 1200).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 42);
+        assert!(bridge.dry_run_write(&control) == Some(DryRunWrite::RpmTarget(1200)));
+
+        bridge.set_mode(&control, &Mode::Manual).unwrap();
+        assert!(bridge.dry_run_write(&control).is_none());
+        bridge.set_value(&control, 50).unwrap();
+        assert!(bridge.get_control_value(&control).unwrap() == 50);
+    }
+}
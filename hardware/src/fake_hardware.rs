@@ -26,6 +26,7 @@ impl HardwareBridge for FakeHardwareBridge {
             name: "temp1".into(),
             hardware_id: "temp1".into(),
             info: String::new(),
+            crit: None,
             internal_index: TEMP_INTERNAL_INDEX,
         };
         hardware.temps.push(temp1.into());
@@ -34,6 +35,7 @@ impl HardwareBridge for FakeHardwareBridge {
             name: "temp2".into(),
             hardware_id: "temp2".into(),
             info: String::new(),
+            crit: None,
             internal_index: TEMP_INTERNAL_INDEX,
         };
         hardware.temps.push(temp2.into());
@@ -42,6 +44,7 @@ impl HardwareBridge for FakeHardwareBridge {
             name: "fan1".into(),
             hardware_id: "fan1".into(),
             info: String::new(),
+            crit: None,
             internal_index: FAN_INTERNAL_INDEX,
         };
         hardware.fans.push(fan1.into());
@@ -50,6 +53,7 @@ impl HardwareBridge for FakeHardwareBridge {
             name: "control1".into(),
             hardware_id: "control1".into(),
             info: String::new(),
+            rpm_target: false,
             internal_index: CONTROL_INTERNAL_INDEX,
         };
         hardware.controls.push(control1.into());
@@ -58,6 +62,7 @@ impl HardwareBridge for FakeHardwareBridge {
             name: "control2".into(),
             hardware_id: "control2".into(),
             info: String::new(),
+            rpm_target: false,
             internal_index: CONTROL_INTERNAL_INDEX,
         };
         hardware.controls.push(control2.into());
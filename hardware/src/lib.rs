@@ -9,20 +9,30 @@ extern crate log;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(target_os = "linux")]
+pub mod system;
+
 #[cfg(target_os = "windows")]
 pub mod windows;
 
 #[cfg(feature = "fake_hardware")]
 pub mod fake_hardware;
 
+pub mod dry_run;
+
 #[derive(Error, Debug)]
 pub enum HardwareError {
     #[cfg(target_os = "linux")]
     #[error(transparent)]
     Linux(#[from] linux::LinuxError),
+    #[cfg(target_os = "linux")]
+    #[error(transparent)]
+    System(#[from] system::SystemError),
     #[cfg(target_os = "windows")]
     #[error(transparent)]
     Windows(#[from] windows::WindowsError),
+    #[error("{0} is not supported by this hardware")]
+    Unsupported(String),
 }
 
 type Result<T> = std::result::Result<T, HardwareError>;
@@ -42,6 +52,10 @@ pub struct HSensor {
     #[serde(skip)]
     pub info: String,
 
+    /// Critical limit reported by the driver, if any.
+    #[serde(skip)]
+    pub crit: Option<Value>,
+
     #[serde(skip)]
     internal_index: usize,
 }
@@ -69,6 +83,10 @@ pub struct HControl {
     #[serde(skip)]
     pub info: String,
 
+    /// The driver can regulate the speed itself, see `set_rpm_target`.
+    #[serde(skip)]
+    pub rpm_target: bool,
+
     #[serde(skip)]
     internal_index: usize,
 }
@@ -107,6 +125,9 @@ pub struct Hardware {
     pub fans: Vec<Rc<HSensor>>,
     #[serde(default, rename = "Temp")]
     pub temps: Vec<Rc<HSensor>>,
+    /// Utilization of the system, in %
+    #[serde(default, rename = "Metric")]
+    pub metrics: Vec<Rc<HSensor>>,
 }
 
 pub type Value = i32;
@@ -115,6 +136,8 @@ pub type Value = i32;
 pub enum Mode {
     Auto,
     Manual,
+    /// The driver regulate the speed to the target set with `set_rpm_target`
+    Rpm,
     Specific(Value),
 }
 
@@ -143,9 +166,26 @@ pub trait HardwareBridge {
     fn get_sensor_value(&mut self, sensor: &HSensor) -> Result<Value>;
     fn get_control_value(&mut self, control: &HControl) -> Result<Value>;
 
+    /// Read a sensor of `Hardware::metrics`.
+    fn get_metric_value(&mut self, metric: &HSensor) -> Result<Value> {
+        Err(HardwareError::Unsupported(format!(
+            "metric {}",
+            metric.name
+        )))
+    }
+
     fn set_value(&mut self, control: &HControl, value: Value) -> Result<()>;
     fn set_mode(&mut self, control: &HControl, mode: &Mode) -> Result<()>;
 
+    /// Set the speed in RPM the driver must reach, for controls with
+    /// `rpm_target`. The mode must be `Mode::Rpm`.
+    fn set_rpm_target(&mut self, control: &HControl, _rpm: Value) -> Result<()> {
+        Err(HardwareError::Unsupported(format!(
+            "RPM target of {}",
+            control.name
+        )))
+    }
+
     /// Used on Windows, because we update all sensors in one function, so
     /// we don't want to update at each call, instead, we call this function
     /// one time in each update iteration.
@@ -157,4 +197,14 @@ pub trait HardwareBridge {
     fn shutdown(&mut self) -> Result<()> {
         Ok(())
     }
+
+    /// Nothing is written to the hardware, see `dry_run::DryRunBridge`.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    /// What would have been written last to this control, during a dry run.
+    fn dry_run_write(&self, _control: &HControl) -> Option<dry_run::DryRunWrite> {
+        None
+    }
 }
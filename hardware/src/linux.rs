@@ -1,9 +1,11 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{fmt::Debug, fs, path::PathBuf, rc::Rc};
 
 use lm_sensors::{feature, value, ChipRef, FeatureRef, LMSensors, SubFeatureRef};
 use thiserror::Error;
 
-use crate::{HControl, HSensor, Hardware, HardwareBridge, HardwareError, Mode, Value};
+use crate::{
+    system::SystemMetrics, HControl, HSensor, Hardware, HardwareBridge, HardwareError, Mode, Value,
+};
 use ouroboros::self_referencing;
 
 // https://www.kernel.org/doc/Documentation/hwmon/sysfs-interface
@@ -12,8 +14,21 @@ use ouroboros::self_referencing;
 static DEFAULT_PWM_ENABLE: f64 = 5.0;
 static MANUAL_MODE: f64 = 1.0;
 
+/// Value of `pwm*_enable` for the closed loop on `fan*_target`. Its meaning
+/// depend on the driver, so it is only used for the ones known here, by
+/// the name of their hwmon device.
+fn rpm_mode(driver: &str) -> Option<f64> {
+    match driver {
+        // "Fan Speed Cruise" mode, 2 is "Thermal Cruise"
+        "nct6106" | "nct6116" | "nct6775" | "nct6776" | "nct6779" | "nct6791" | "nct6792"
+        | "nct6793" | "nct6795" | "nct6796" | "nct6797" | "nct6798" | "nct6799" => Some(3.0),
+        _ => None,
+    }
+}
+
 pub struct LinuxBridge {
     lm_sensor: LinuxBridgeSelfRef,
+    system: SystemMetrics,
     hardware: Hardware,
 }
 
@@ -21,6 +36,8 @@ pub struct LinuxBridge {
 pub enum LinuxError {
     #[error("{0}: {1}")]
     LmSensors(String, lm_sensors::errors::Error),
+    #[error("{0}: {1}")]
+    Io(String, std::io::Error),
 }
 
 #[self_referencing]
@@ -47,6 +64,9 @@ struct PwmRefs<'a> {
     io: SubFeatureRef<'a>,
     enable: SubFeatureRef<'a>,
     default_enable_cached: f64,
+    /// `fan*_target` file, not exposed by libsensors, and
+    /// the value of `pwm*_enable` to use it
+    target: Option<(PathBuf, f64)>,
 }
 struct SensorRefs<'a> {
     io: SubFeatureRef<'a>,
@@ -137,6 +157,7 @@ fn generate_hardware<'a>(
                                     name: h_info.name,
                                     hardware_id: h_info.hardware_id,
                                     info: h_info.info,
+                                    crit: None,
                                     internal_index: next_internal_index,
                                 }));
                             }
@@ -152,6 +173,13 @@ fn generate_hardware<'a>(
                             continue;
                         };
 
+                        // the critical limit is static, so we only read it once
+                        let crit = feature_ref
+                            .sub_feature_by_kind(value::Kind::TemperatureCritical)
+                            .and_then(|crit_ref| crit_ref.raw_value())
+                            .ok()
+                            .map(|crit| crit as Value);
+
                         match get_infos_from_refs(&chip_ref, &feature_ref, &sub_feature_ref) {
                             Ok(h_info) => {
                                 let sensor = SensorRefs {
@@ -162,6 +190,7 @@ fn generate_hardware<'a>(
                                     name: h_info.name,
                                     hardware_id: h_info.hardware_id,
                                     info: h_info.info,
+                                    crit,
                                     internal_index: next_internal_index,
                                 }));
                             }
@@ -196,18 +225,48 @@ fn generate_hardware<'a>(
                             }
                         };
 
+                        // pwmN regulate fanN
+                        let target = chip_ref
+                            .path()
+                            .zip(feature_ref.name().and_then(|name| name.ok()))
+                            .map(|(path, name)| {
+                                path.join(format!("{}_target", name.replacen("pwm", "fan", 1)))
+                            })
+                            .filter(|target| target.exists());
+
+                        // never guess the mode of an unknown driver
+                        let target = target.and_then(|target| {
+                            let driver = chip_ref
+                                .path()
+                                .and_then(|path| fs::read_to_string(path.join("name")).ok())?;
+                            match rpm_mode(driver.trim()) {
+                                Some(mode) => Some((target, mode)),
+                                None => {
+                                    info!(
+                                        "{} is not used: the RPM mode of {} is unknown.",
+                                        target.display(),
+                                        driver.trim()
+                                    );
+                                    None
+                                }
+                            }
+                        });
+
                         match get_infos_from_refs(&chip_ref, &feature_ref, &sub_feature_ref_io) {
                             Ok(h_info) => {
+                                let rpm_target = target.is_some();
                                 let sensor = InternalSubFeatureRef::Pwm(PwmRefs {
                                     io: sub_feature_ref_io,
                                     enable: sub_feature_ref_enable,
                                     default_enable_cached: enable_cached,
+                                    target,
                                 });
                                 sensors.push(sensor);
                                 hardware.controls.push(Rc::new(HControl {
                                     name: h_info.name,
                                     hardware_id: h_info.hardware_id,
                                     info: h_info.info,
+                                    rpm_target,
                                     internal_index: next_internal_index,
                                 }));
                             }
@@ -244,8 +303,12 @@ impl HardwareBridge for LinuxBridge {
         }
         .build();
 
+        let system = SystemMetrics::new("/proc");
+        hardware.metrics = system.sensors().into_iter().map(Rc::new).collect();
+
         Ok(Self {
             lm_sensor: bridge,
+            system,
             hardware,
         })
     }
@@ -267,6 +330,10 @@ impl HardwareBridge for LinuxBridge {
             }
         })
     }
+    fn get_metric_value(&mut self, metric: &HSensor) -> crate::Result<Value> {
+        Ok(self.system.get_value(metric.internal_index)?)
+    }
+
     fn get_control_value(&mut self, control: &HControl) -> crate::Result<Value> {
         self.lm_sensor.with_sensors(|sensors| {
             match sensors
@@ -315,6 +382,15 @@ impl HardwareBridge for LinuxBridge {
                     let value = match mode {
                         Mode::Auto => pwm_refs.default_enable_cached,
                         Mode::Manual => MANUAL_MODE,
+                        Mode::Rpm => match &pwm_refs.target {
+                            Some((_, rpm_mode)) => *rpm_mode,
+                            None => {
+                                return Err(HardwareError::Unsupported(format!(
+                                    "RPM mode of {}",
+                                    control.name
+                                )))
+                            }
+                        },
                         Mode::Specific(value) => value.to_owned().into(),
                     };
 
@@ -329,4 +405,34 @@ impl HardwareBridge for LinuxBridge {
             }
         })
     }
+
+    fn set_rpm_target(&mut self, control: &HControl, rpm: Value) -> crate::Result<()> {
+        self.lm_sensor.with_sensors(|sensors| {
+            match sensors
+                .get(control.internal_index)
+                .expect("no sensor found")
+            {
+                InternalSubFeatureRef::Pwm(pwm_refs) => {
+                    let Some((target, _)) = &pwm_refs.target else {
+                        return Err(HardwareError::Unsupported(format!(
+                            "RPM target of {}",
+                            control.name
+                        )));
+                    };
+
+                    if let Err(e) = fs::write(target, rpm.to_string()) {
+                        let explication = format!("can't set target {} to a fan", rpm);
+                        return Err(HardwareError::Linux(LinuxError::Io(explication, e)));
+                    }
+                    Ok(())
+                }
+                _ => unreachable!(),
+            }
+        })
+    }
+
+    fn update(&mut self) -> crate::Result<()> {
+        self.system.update();
+        Ok(())
+    }
 }
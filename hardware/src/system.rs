@@ -0,0 +1,264 @@
+use std::{fs, path::PathBuf};
+
+use thiserror::Error;
+
+use crate::{HSensor, Value};
+
+#[derive(Error, Debug)]
+pub enum SystemError {
+    #[error("{0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("Invalid data in {0}")]
+    InvalidData(String),
+}
+
+type Result<T> = std::result::Result<T, SystemError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    /// Busy time of all CPUs since the last update, in %
+    CpuUsage,
+    /// Load average over 1 minute, in % of the number of CPUs
+    LoadAverage,
+    /// Time some tasks were stalled on this resource, over 10 seconds (PSI)
+    Pressure(&'static str),
+}
+
+/// Metrics of the system, read from procfs. The utilization rise
+/// before the temperatures do, so fans can be ramped earlier.
+pub struct SystemMetrics {
+    proc_root: PathBuf,
+    metrics: Vec<Metric>,
+    /// Values sampled since the last update
+    values: Vec<Option<Value>>,
+    /// Busy and total time of the CPUs, at the last sample
+    cpu_times: Option<(u64, u64)>,
+}
+
+impl SystemMetrics {
+    /// `proc_root` is "/proc", except in tests. Only the metrics
+    /// available on this system are exposed.
+    pub fn new(proc_root: impl Into<PathBuf>) -> Self {
+        let proc_root: PathBuf = proc_root.into();
+        let mut metrics = Vec::new();
+
+        if proc_root.join("stat").exists() {
+            metrics.push(Metric::CpuUsage);
+
+            if proc_root.join("loadavg").exists() {
+                metrics.push(Metric::LoadAverage);
+            }
+        }
+
+        for resource in ["cpu", "memory", "io"] {
+            if proc_root.join("pressure").join(resource).exists() {
+                metrics.push(Metric::Pressure(resource));
+            }
+        }
+
+        Self {
+            proc_root,
+            values: vec![None; metrics.len()],
+            metrics,
+            cpu_times: None,
+        }
+    }
+
+    /// Sensors of the metrics, the internal index is the one
+    /// expected by `get_value`.
+    pub fn sensors(&self) -> Vec<HSensor> {
+        self.metrics
+            .iter()
+            .enumerate()
+            .map(|(internal_index, metric)| {
+                let (name, hardware_id, file) = match metric {
+                    Metric::CpuUsage => (
+                        "CPU usage".to_owned(),
+                        "cpu-usage".to_owned(),
+                        "stat".to_owned(),
+                    ),
+                    Metric::LoadAverage => (
+                        "Load average".to_owned(),
+                        "load-average".to_owned(),
+                        "loadavg".to_owned(),
+                    ),
+                    Metric::Pressure(resource) => (
+                        format!("{} pressure", resource),
+                        format!("{}-pressure", resource),
+                        format!("pressure/{}", resource),
+                    ),
+                };
+
+                HSensor {
+                    name,
+                    hardware_id: format!("system-{}", hardware_id),
+                    info: format!("source: {}", self.proc_root.join(file).display()),
+                    crit: None,
+                    internal_index,
+                }
+            })
+            .collect()
+    }
+
+    /// Start a new sample. Metrics are read at most once between
+    /// two updates, so the CPU usage cover the whole period.
+    pub fn update(&mut self) {
+        self.values.fill(None);
+    }
+
+    pub fn get_value(&mut self, internal_index: usize) -> Result<Value> {
+        if let Some(value) = self.values[internal_index] {
+            return Ok(value);
+        }
+
+        let value = self.sample(self.metrics[internal_index])?;
+        self.values[internal_index] = Some(value);
+        Ok(value)
+    }
+
+    fn sample(&mut self, metric: Metric) -> Result<Value> {
+        match metric {
+            Metric::CpuUsage => {
+                let (busy, total) = self.cpu_times()?;
+
+                let usage = match self.cpu_times.replace((busy, total)) {
+                    Some((last_busy, last_total)) if total > last_total => {
+                        busy.saturating_sub(last_busy) * 100 / (total - last_total)
+                    }
+                    // first sample: usage since the boot
+                    _ => busy * 100 / total.max(1),
+                };
+
+                Ok(usage as Value)
+            }
+            Metric::LoadAverage => {
+                let load: f32 = self
+                    .read("loadavg")?
+                    .split_whitespace()
+                    .next()
+                    .and_then(|load| load.parse().ok())
+                    .ok_or_else(|| SystemError::InvalidData("loadavg".to_owned()))?;
+
+                let nb_cpu = self
+                    .read("stat")?
+                    .lines()
+                    .filter(|line| line.starts_with("cpu") && !line.starts_with("cpu "))
+                    .count()
+                    .max(1);
+
+                Ok((load * 100.0 / nb_cpu as f32).round() as Value)
+            }
+            Metric::Pressure(resource) => {
+                let file = format!("pressure/{}", resource);
+
+                // some avg10=1.23 avg60=0.50 avg300=0.10 total=123456
+                let avg10: f32 = self
+                    .read(&file)?
+                    .lines()
+                    .find(|line| line.starts_with("some "))
+                    .and_then(|line| {
+                        line.split_whitespace()
+                            .find_map(|field| field.strip_prefix("avg10="))
+                    })
+                    .and_then(|avg10| avg10.parse().ok())
+                    .ok_or(SystemError::InvalidData(file))?;
+
+                Ok(avg10.round() as Value)
+            }
+        }
+    }
+
+    /// Busy and total time of all CPUs, from the first line of `stat`
+    fn cpu_times(&self) -> Result<(u64, u64)> {
+        let stat = self.read("stat")?;
+
+        // cpu user nice system idle iowait irq softirq steal guest guest_nice
+        // guest times are already counted in user and nice
+        let times: Vec<u64> = stat
+            .lines()
+            .find(|line| line.starts_with("cpu "))
+            .map(|line| {
+                line.split_whitespace()
+                    .skip(1)
+                    .take(8)
+                    .filter_map(|time| time.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if times.len() < 4 {
+            return Err(SystemError::InvalidData("stat".to_owned()));
+        }
+
+        let idle = times[3] + times.get(4).unwrap_or(&0);
+        let total: u64 = times.iter().sum();
+
+        Ok((total - idle, total))
+    }
+
+    fn read(&self, file: &str) -> Result<String> {
+        let path = self.proc_root.join(file);
+        fs::read_to_string(&path).map_err(|e| SystemError::Io(path.display().to_string(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::SystemMetrics;
+
+    fn init_test_logging() {
+        let _ = env_logger::builder().format_timestamp(None).try_init();
+    }
+
+    #[test]
+    fn test_metrics() {
+        init_test_logging();
+
+        let proc_root =
+            std::env::temp_dir().join(format!("fan-control-proc-{}", std::process::id()));
+        fs::create_dir_all(proc_root.join("pressure")).unwrap();
+
+        let stat = |user: u64, idle: u64| {
+            format!("cpu  {user} 0 0 {idle} 0 0 0 0 0 0\ncpu0 0 0 0 0 0 0 0 0 0 0\ncpu1 0 0 0 0 0 0 0 0 0 0\n")
+        };
+        fs::write(proc_root.join("stat"), stat(100, 300)).unwrap();
+        fs::write(proc_root.join("loadavg"), "1.50 0.80 0.40 2/300 1234\n").unwrap();
+        fs::write(
+            proc_root.join("pressure/memory"),
+            "some avg10=12.60 avg60=3.00 avg300=1.00 total=1000\nfull avg10=5.00 avg60=1.00 avg300=0.50 total=500\n",
+        )
+        .unwrap();
+
+        let mut metrics = SystemMetrics::new(&proc_root);
+        let sensors = metrics.sensors();
+
+        let ids: Vec<&str> = sensors.iter().map(|s| s.hardware_id.as_str()).collect();
+        assert!(
+            ids == [
+                "system-cpu-usage",
+                "system-load-average",
+                "system-memory-pressure"
+            ]
+        );
+
+        // since the boot
+        assert!(metrics.get_value(0).unwrap() == 25);
+        // 1.5 on 2 cpus
+        assert!(metrics.get_value(1).unwrap() == 75);
+        assert!(metrics.get_value(2).unwrap() == 13);
+
+        // 60 busy on 100
+        fs::write(proc_root.join("stat"), stat(160, 340)).unwrap();
+        assert!(metrics.get_value(0).unwrap() == 25);
+        metrics.update();
+        assert!(metrics.get_value(0).unwrap() == 60);
+
+        fs::write(proc_root.join("pressure/memory"), "invalid").unwrap();
+        metrics.update();
+        assert!(metrics.get_value(2).is_err());
+
+        fs::remove_dir_all(proc_root).unwrap();
+    }
+}
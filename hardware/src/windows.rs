@@ -166,18 +166,21 @@ fn read_hardware(stream: &TcpStream) -> Result<Hardware> {
                 name: base_hardware.name,
                 hardware_id: base_hardware.id,
                 info: String::new(),
+                rpm_target: false,
                 internal_index: base_hardware.index,
             })),
             HardwareType::Fan => hardware.fans.push(Rc::new(HSensor {
                 name: base_hardware.name,
                 hardware_id: base_hardware.id,
                 info: String::new(),
+                crit: None,
                 internal_index: base_hardware.index,
             })),
             HardwareType::Temp => hardware.temps.push(Rc::new(HSensor {
                 name: base_hardware.name,
                 hardware_id: base_hardware.id,
                 info: String::new(),
+                crit: None,
                 internal_index: base_hardware.index,
             })),
         }
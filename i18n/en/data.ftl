@@ -2,21 +2,50 @@ none = None
 average = Average
 max = Max
 min = Min
+sum = Sum
+moving_average = Moving average
+ema = Exponential average
+step = Step
+linear = Linear
+spline = Spline
 
 default_control = Control
 default_fan = Fan
 default_temp = Temp
+default_metric = Metric
 default_custom_temp = Custom temp
 default_graph = Graph
 default_flat = Flat
 default_linear = Linear
 default_target = Target
+default_pid = PID
+default_expression = Expression
+default_mix = Mix
+default_switch = Switch
+default_schedule = Schedule
+default_filter = Filter
+default_delta = Delta
+
+mon = Mon
+tue = Tue
+wed = Wed
+thu = Thu
+fri = Fri
+sat = Sat
+sun = Sun
 
 system_theme = System
 dark_theme = Dark
 light_theme = Light
 
+celsius = Celsius (°C)
+fahrenheit = Fahrenheit (°F)
+percent = Percent (%)
+pwm = Raw PWM (0-255)
+
 value_celsius = { $value } °C
 value_percentage = { $value } %
+value_fahrenheit = { $value } °F
+value_pwm = { $value } PWM
 value_rpm = { $value } RPM
 no_value = No value
\ No newline at end of file
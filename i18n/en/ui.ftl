@@ -1,12 +1,18 @@
 none = None
 delete = Delete
 settings = Settings
+dry_run = Dry run
+dry_run_write = Would write { $value }
 name = Name
 value = Value
 theme = Theme
 update_delay = Update delay
 update_delay_value = { $value } ms
+temp_unit = Temperature unit
+speed_unit = Speed unit
+behavior_selection = Behavior selection
 temp_selection = Temp selection
+add_member = Add member
 min_temp = min temp
 min_speed = min speed
 max_temp = max temp
@@ -15,12 +21,30 @@ idle_temp = idle temp
 idle_speed = idle speed
 load_temp = load temp
 load_speed = load speed
+setpoint = setpoint
+kp = Kp
+ki = Ki
+kd = Kd
+anti_windup = Anti-windup
+span = span
+expression = expression
+delta_input = input
+delta_reference = reference
+schedule_default = outside of the periods
+schedule_override_condition = lift the caps with
+schedule_override_threshold = from
+switch_condition = condition
+switch_threshold = threshold
+switch_hysteresis = hysteresis
+switch_above = above the threshold
+switch_below = below the threshold
 launch_graph_window = Add coordinates
 
 # Add item description
 add_item = Add an item
 add_fan = Monitor a fan sensor
 add_temp = Monitor a temp sensor
+add_metric = Monitor the load of the system (CPU usage, load average, pressure)
 add_custom_temp = Define logic between values (Max, Averrage, ...)
 add_control = Assigns a certain behavior to a certain hardware component
 add_flat = Returns a fixed value
@@ -37,6 +61,33 @@ add_target = Take 5 variables:
     - a sensor value
     If the sensor > trigger temperature, trigger speed is set
     until this sensor is < ideal temperature
+add_pid = Hold a sensor at a setpoint temperature:
+    - a setpoint temp
+    - the proportional, integral and derivative gains
+    - a min and a max speed
+    - a sensor value
+    The speed increase while the sensor is above the setpoint,
+    taking into account the time between updates
+add_filter = Smooth a temp over a time span:
+    - a moving average
+    - an exponential moving average
+    - or the max of the span
+add_expression = Evaluate an expression over named inputs,
+    for example: max(cpu, gpu - 10) * 1.2
+    Available: + - * / < <= > >= == != !,
+    min, max, clamp(x, min, max), abs(x), if(cond, a, b)
+    Names with spaces must be quoted: "CPU temp"
+add_mix = Combine several behaviors (Max, Min, Average, Sum)
+add_delta = Difference between two temps: input - reference,
+    for example a GPU temp minus the ambient temp
+add_schedule = Select a behavior depending on the time and weekday,
+    for example a quiet curve from 22:00 to 07:00.
+    A period can also cap the speed.
+    Periods are defined in the configuration file
+add_switch = Select a behavior depending on a condition,
+    for example: if the GPU temp > 60 use curve A, otherwise curve B.
+    The hysteresis prevent switching back and forth:
+    curve B is used again once the condition < threshold - hysteresis
 add_graph = Graph
 
 # Config
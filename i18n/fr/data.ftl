@@ -2,21 +2,50 @@ none = Aucun
 average = Moyenne
 max = Max
 min = Min
+sum = Somme
+moving_average = Moyenne glissante
+ema = Moyenne exponentielle
+step = Palier
+linear = Linéaire
+spline = Spline
 
 default_control = Control
 default_fan = Ventilateur
 default_temp = Temp
+default_metric = Métrique
 default_custom_temp = Temp custom
 default_graph = Graph
 default_flat = Plat
 default_linear = Lineaire
 default_target = Cible
+default_pid = PID
+default_expression = Expression
+default_mix = Mélange
+default_switch = Condition
+default_schedule = Horaire
+default_filter = Filtre
+default_delta = Écart
+
+mon = Lun
+tue = Mar
+wed = Mer
+thu = Jeu
+fri = Ven
+sat = Sam
+sun = Dim
 
 system_theme = Système
 dark_theme = Sombre
 light_theme = Clair
 
+celsius = Celsius (°C)
+fahrenheit = Fahrenheit (°F)
+percent = Pourcentage (%)
+pwm = PWM brut (0-255)
+
 value_celsius = { $value } °C
 value_percentage = { $value } %
+value_fahrenheit = { $value } °F
+value_pwm = { $value } PWM
 value_rpm = { $value } RPM
 no_value = Aucune valeur
\ No newline at end of file
@@ -1,12 +1,18 @@
 none = Aucun
 delete = Supprimer
 settings = Paramètre
+dry_run = Simulation
+dry_run_write = Écrirait { $value }
 name = Nom
 value = Valeur
 theme = Thème
 update_delay = Délai de mise à jour
 update_delay_value = { $value } ms
+temp_unit = Unité de température
+speed_unit = Unité de vitesse
+behavior_selection = Sélection de comportement
 temp_selection = Temp selection
+add_member = Ajouter un membre
 min_temp = min temp
 min_speed = min speed
 max_temp = max temp
@@ -15,12 +21,30 @@ idle_temp = idle temp
 idle_speed = idle speed
 load_temp = load temp
 load_speed = load speed
+setpoint = consigne
+kp = Kp
+ki = Ki
+kd = Kd
+anti_windup = Anti-saturation
+span = durée
+expression = expression
+delta_input = entrée
+delta_reference = référence
+schedule_default = en dehors des périodes
+schedule_override_condition = lever les limites avec
+schedule_override_threshold = à partir de
+switch_condition = condition
+switch_threshold = seuil
+switch_hysteresis = hystérésis
+switch_above = au-dessus du seuil
+switch_below = en dessous du seuil
 launch_graph_window = Ajout de coordonnées
 
 # Add item description
 add_item = Ajouter un item
 add_fan = Monitore un ventilateur
 add_temp = Monitore un capteur de temperature
+add_metric = Monitore la charge du système (utilisation CPU, charge moyenne, pression)
 add_custom_temp = Defini une logique entre des valeurs (Max, Moyenne, ...)
 add_control = Applique un certain comportement a un ventilateur
 add_flat = Retourne une valeur fixe
@@ -37,6 +61,33 @@ add_target = Prend 5 variables :
     - une valeur de capteur
     Si le capteur > température de déclenchement, la vitesse de déclenchement est définie
     jusqu'à ce que ce capteur < température idéale
+add_pid = Maintient un capteur à une température de consigne :
+    - une température de consigne
+    - les gains proportionnel, intégral et dérivé
+    - une vitesse minimale et maximale
+    - une valeur de capteur
+    La vitesse augmente tant que le capteur est au-dessus de la consigne,
+    en tenant compte du temps entre les mises à jour
+add_filter = Lisse une température sur une durée :
+    - une moyenne glissante
+    - une moyenne glissante exponentielle
+    - ou le max sur la durée
+add_expression = Évalue une expression sur des entrées nommées,
+    par exemple : max(cpu, gpu - 10) * 1.2
+    Disponible : + - * / < <= > >= == != !,
+    min, max, clamp(x, min, max), abs(x), if(cond, a, b)
+    Les noms avec des espaces doivent être entre guillemets : "CPU temp"
+add_mix = Combine plusieurs comportements (Max, Min, Moyenne, Somme)
+add_delta = Différence entre deux températures : entrée - référence,
+    par exemple la température du GPU moins la température ambiante
+add_schedule = Sélectionne un comportement selon l'heure et le jour,
+    par exemple une courbe silencieuse de 22:00 à 07:00.
+    Une période peut aussi limiter la vitesse.
+    Les périodes sont définies dans le fichier de configuration
+add_switch = Sélectionne un comportement selon une condition,
+    par exemple : si la température du GPU > 60 utilise la courbe A, sinon la courbe B.
+    L'hystérésis évite de basculer sans arrêt :
+    la courbe B est de nouveau utilisée quand la condition < seuil - hystérésis
 add_graph = Graphe
 
 # Config
@@ -30,6 +30,13 @@ pub struct Args {
     )]
     pub cli: bool,
 
+    #[arg(
+        long = "dry-run",
+        default_value_t = false,
+        help = "Evaluate the config without writing anything to the hardware. Values that would have been written are logged"
+    )]
+    pub dry_run: bool,
+
     #[arg(
         long = "debug",
         default_value_t = false,
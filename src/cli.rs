@@ -11,7 +11,7 @@ use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use data::{settings::Settings, AppState};
 use hardware::HardwareBridge;
 
-pub fn run_cli<H: HardwareBridge>(mut app_state: AppState<H>) {
+pub fn run_cli<H: HardwareBridge>(mut app_state: AppState<H>, dry_run: bool) {
     let current_config = match &app_state.dir_manager.settings().current_config {
         Some(current_config) => current_config,
         None => {
@@ -27,7 +27,7 @@ pub fn run_cli<H: HardwareBridge>(mut app_state: AppState<H>) {
 
     let (tx, rx) = mpsc::channel::<UserAction>();
     start_listening(tx);
-    display_info(app_state.dir_manager.settings(), current_config);
+    display_info(app_state.dir_manager.settings(), current_config, dry_run);
 
     loop {
         if let Err(e) = app_state.bridge.update() {
@@ -36,14 +36,17 @@ pub fn run_cli<H: HardwareBridge>(mut app_state: AppState<H>) {
         }
         std::thread::sleep(H::TIME_TO_UPDATE);
 
-        if let Err(e) = app_state.update.optimized(
-            &mut app_state.app_graph.nodes,
-            &app_state.app_graph.root_nodes,
-            &mut app_state.bridge,
-        ) {
+        if let Err(e) = app_state
+            .update
+            .optimized(&mut app_state.app_graph, &mut app_state.bridge)
+        {
             error!("{}", e);
         }
 
+        if let Some(config_name) = app_state.auto_switch_config() {
+            println!("Active configuration: {}", config_name);
+        }
+
         let settings_update_delay =
             Duration::from_millis(app_state.dir_manager.settings().update_delay)
                 - H::TIME_TO_UPDATE;
@@ -100,10 +103,18 @@ fn start_listening(tx: Sender<UserAction>) {
     });
 }
 
-fn display_info(settings: &Settings, current_config: &String) {
+fn display_info(settings: &Settings, current_config: &String, dry_run: bool) {
     println!();
     println!("Update delay: {} ms", settings.update_delay);
+    println!(
+        "Units: {}, {}",
+        settings.temp_unit.to_string(),
+        settings.speed_unit.to_string()
+    );
     println!("Active configuration: {}", current_config);
+    if dry_run {
+        println!("Dry run: values are logged instead of being written to the hardware");
+    }
     println!();
     println!("Available options:");
     println!("quit: q");
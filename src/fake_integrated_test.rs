@@ -32,6 +32,7 @@ fn test_config() {
         app_graph,
         update: Update::new(),
         bridge,
+        auto_switcher: Default::default(),
     };
 
     run(app_state)
@@ -48,11 +49,7 @@ fn run<H: HardwareBridge>(mut app_state: AppState<H>) {
 
         app_state
             .update
-            .optimized(
-                &mut app_state.app_graph.nodes,
-                &app_state.app_graph.root_nodes,
-                &mut app_state.bridge,
-            )
+            .optimized(&mut app_state.app_graph, &mut app_state.bridge)
             .unwrap();
         debug!("\n");
         thread::sleep(Duration::from_millis(50));
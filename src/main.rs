@@ -7,7 +7,7 @@ use std::{env, fs};
 use args::Args;
 use clap::Parser;
 use data::{app_graph::AppGraph, dir_manager::DirManager, update::Update, AppState};
-use hardware::{self, HardwareBridge};
+use hardware::{self, dry_run::DryRunBridge, HardwareBridge};
 use log::LevelFilter;
 use thiserror::Error;
 
@@ -56,6 +56,11 @@ fn setup_logs(args: &Args) {
         filter_workspace_crates(&mut builder, LevelFilter::Debug);
     };
 
+    if args.dry_run {
+        // values that would have been written must always be visible
+        builder.filter_module("hardware::dry_run", LevelFilter::Info);
+    }
+
     if let Some(log_file_path) = &args.log_file {
         env::set_var("FAN_CONTROL_LOG_FILE", log_file_path);
         match fs::File::create(log_file_path) {
@@ -91,6 +96,18 @@ fn try_run() -> Result<()> {
     let dir_manager = DirManager::new(&args.config_dir_path, &args.config_name);
 
     let bridge = hardware::new()?;
+
+    if args.dry_run {
+        info!("Dry run: nothing will be written to the hardware.");
+        run(&args, dir_manager, DryRunBridge::wrap(bridge));
+    } else {
+        run(&args, dir_manager, bridge);
+    }
+
+    Ok(())
+}
+
+fn run<H: HardwareBridge + 'static>(args: &Args, dir_manager: DirManager, bridge: H) {
     let hardware = bridge.hardware();
 
     debug!("sensors found: {:?}", hardware);
@@ -107,14 +124,13 @@ fn try_run() -> Result<()> {
         bridge,
         app_graph,
         update: Update::new(),
+        auto_switcher: Default::default(),
     };
 
     match args.cli {
-        true => cli::run_cli(app_state),
+        true => cli::run_cli(app_state, args.dry_run),
         false => ui::run_ui(app_state),
     };
-
-    Ok(())
 }
 
 fn main() {
@@ -12,11 +12,19 @@ pub fn add_node_button_view(expanded: bool) -> Element<'static, AppMsg> {
             .push(add_item(NodeTypeLight::Control, fl!("add_control")))
             .push(add_item(NodeTypeLight::Fan, fl!("add_fan")))
             .push(add_item(NodeTypeLight::Temp, fl!("add_temp")))
+            .push(add_item(NodeTypeLight::Metric, fl!("add_metric")))
             .push(add_item(NodeTypeLight::CustomTemp, fl!("add_custom_temp")))
+            .push(add_item(NodeTypeLight::Filter, fl!("add_filter")))
+            .push(add_item(NodeTypeLight::Delta, fl!("add_delta")))
+            .push(add_item(NodeTypeLight::Expression, fl!("add_expression")))
             .push(add_item(NodeTypeLight::Linear, fl!("add_linear")))
             .push(add_item(NodeTypeLight::Target, fl!("add_target")))
+            .push(add_item(NodeTypeLight::Pid, fl!("add_pid")))
             .push(add_item(NodeTypeLight::Graph, fl!("add_graph")))
             .push(add_item(NodeTypeLight::Flat, fl!("add_flat")))
+            .push(add_item(NodeTypeLight::Mix, fl!("add_mix")))
+            .push(add_item(NodeTypeLight::Switch, fl!("add_switch")))
+            .push(add_item(NodeTypeLight::Schedule, fl!("add_schedule")))
             .push(icon_button("close/40").on_press(AppMsg::Toggle(ToogleMsg::CreateButton(false))))
             .into(),
 
@@ -7,9 +7,10 @@ use cosmic::{
 };
 use data::{
     app_graph::Nodes,
-    config::graph::{Coord, Graph},
+    config::graph::{Coord, Graph, Interpolation},
     id::Id,
     node::{Input, Node, ValueKind},
+    units::Units,
 };
 
 use crate::{
@@ -24,6 +25,7 @@ pub fn graph_view<'a>(
     graph: &'a Graph,
     _graph_c: &'a GraphC,
     nodes: &'a Nodes,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let input_options =
         pick_list_utils::input::optional_availlable_inputs(nodes, node, graph.input.is_some());
@@ -34,8 +36,32 @@ pub fn graph_view<'a>(
     .width(Length::Fill)
     .into();
 
+    let interpolation_options = Interpolation::VALUES
+        .iter()
+        .filter(|i| &graph.interpolation != *i)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let pick_interpolation = PickList::new(
+        interpolation_options,
+        Some(graph.interpolation.clone()),
+        |i| ModifNodeMsg::Graph(GraphMsg::Interpolation(i)).to_app(node.id),
+    )
+    .width(Length::Fill)
+    .into();
+
+    let kind = coord_kind(nodes, node);
+
     let coords = graph.coords.0.iter().map(|coord| {
-        let text = format!("{}°C = {}%", coord.temp, coord.percent);
+        let temp = units.display_value(coord.temp, &kind);
+        let percent = units.display_text(coord.percent.into(), &ValueKind::Porcentage);
+        let text = format!(
+            "{}{} = {}{}",
+            (temp * 10.0).round() / 10.0,
+            units.symbol(&kind),
+            percent,
+            units.symbol(&ValueKind::Porcentage),
+        );
 
         Row::new()
             .push(Text::new(text).width(Length::Fixed(100.0)))
@@ -59,14 +85,23 @@ pub fn graph_view<'a>(
 
     let content = vec![
         pick_input,
+        pick_interpolation,
         launch_window,
-        Text::new(node.value_text(&ValueKind::Porcentage)).into(),
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
         coords,
     ];
 
     Column::with_children(content).into()
 }
 
+/// Kind of the temp of the coords, the one of the input. °C without input.
+fn coord_kind(nodes: &Nodes, node: &Node) -> ValueKind {
+    node.inputs
+        .first()
+        .and_then(|input| nodes.get(&input.id))
+        .map_or(ValueKind::Celsius, |input| input.value_kind(nodes))
+}
+
 #[derive(Debug, Clone)]
 pub enum GraphWindowMsg {
     Toogle(Option<Id>),
@@ -81,32 +116,45 @@ pub struct GraphWindow {
     pub percent_c: String,
 }
 
+impl GraphWindow {
+    /// The typed coord, converted from the displayed units.
+    fn coord(&self, units: Units, kind: &ValueKind) -> Option<Coord> {
+        let temp = self.temp_c.trim().parse::<f32>().ok()?;
+        let percent = self.percent_c.trim().parse::<f32>().ok()?;
+
+        let temp = units.config_value(temp, kind);
+        let percent = units.config_value(percent, &ValueKind::Porcentage).round();
+
+        Coord::try_from((temp.to_string().as_str(), percent.to_string().as_str())).ok()
+    }
+}
+
 pub fn graph_window_view<'a>(
     graph_window: &'a GraphWindow,
-    _nodes: &'a Nodes,
+    nodes: &'a Nodes,
+    units: Units,
 ) -> Element<'a, AppMsg> {
+    let kind = nodes
+        .get(&graph_window.node_id)
+        .map_or(ValueKind::Celsius, |node| coord_kind(nodes, node));
+
     let temp_input = Row::new()
         .push(
             TextInput::new("temp", &graph_window.temp_c)
                 .on_input(|s| GraphWindowMsg::ChangeTemp(s).into()),
         )
-        .push(text("°C"));
+        .push(text(units.symbol(&kind)));
 
     let percent_input = Row::new()
         .push(
             TextInput::new("percent", &graph_window.percent_c)
                 .on_input(|s| GraphWindowMsg::ChangePercent(s).into()),
         )
-        .push(text("%"));
-
-    let coord = Coord::try_from((
-        graph_window.temp_c.as_ref(),
-        graph_window.percent_c.as_ref(),
-    ));
+        .push(text(units.symbol(&ValueKind::Porcentage)));
 
     let mut add_button = Button::new("add");
 
-    if let Ok(coord) = coord {
+    if let Some(coord) = graph_window.coord(units, &kind) {
         add_button = add_button
             .on_press(ModifNodeMsg::Graph(GraphMsg::AddCoord(coord)).to_app(graph_window.node_id));
     }
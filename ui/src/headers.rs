@@ -137,9 +137,19 @@ fn config_choice_line<'a>(optional_name: Option<String>) -> Element<'a, AppMsg>
         .into()
 }
 
-pub fn header_end<'a>() -> Vec<Element<'a, AppMsg>> {
+pub fn header_end<'a>(dry_run: bool) -> Vec<Element<'a, AppMsg>> {
     let mut elems = vec![];
 
+    if dry_run {
+        // nothing is written to the hardware, make it obvious
+        let dry_run = Container::new(Text::new(fl!("dry_run")))
+            .padding([0, 10])
+            .center_y()
+            .height(ICON_LENGHT)
+            .into();
+        elems.push(dry_run);
+    }
+
     let settings_button = icon_button("settings/40")
         .on_press(AppMsg::Toggle(ToogleMsg::Settings))
         .height(ICON_LENGHT)
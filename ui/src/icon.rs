@@ -43,11 +43,19 @@ pub fn icon_path_for_node_type(node_type: &NodeTypeLight) -> &'static str {
         NodeTypeLight::Control => "speed/24",
         NodeTypeLight::Fan => "toys_fan/24",
         NodeTypeLight::Temp => "thermometer/24",
+        NodeTypeLight::Metric => "monitoring/24",
         NodeTypeLight::CustomTemp => "thermostat/24",
         NodeTypeLight::Graph => "psychology/24",
         NodeTypeLight::Flat => "horizontal_rule/24",
         NodeTypeLight::Linear => "linear/24",
         NodeTypeLight::Target => "my_location/24",
+        NodeTypeLight::Pid => "settings/24",
+        NodeTypeLight::Filter => "filter/24",
+        NodeTypeLight::Delta => "delta/24",
+        NodeTypeLight::Expression => "alternate_email/24",
+        NodeTypeLight::Mix => "mix/24",
+        NodeTypeLight::Switch => "call_split/24",
+        NodeTypeLight::Schedule => "schedule/24",
     }
 }
 
@@ -6,6 +6,8 @@ use cosmic::{
     Element,
 };
 
+use data::{node::ValueKind, units::Units};
+
 use crate::{icon::icon_button, ModifNodeMsg};
 
 pub trait MyFrom<T> {
@@ -27,10 +29,40 @@ impl MyFrom<&str> for Option<u8> {
     }
 }
 
+impl MyFrom<i32> for u16 {
+    fn from(value: i32) -> Self {
+        value as u16
+    }
+}
+
+impl MyFrom<&str> for Option<u16> {
+    fn from(value: &str) -> Self {
+        match value.parse::<u16>() {
+            Ok(value) => Some(value),
+            Err(_) => None,
+        }
+    }
+}
+
+impl MyFrom<i32> for f32 {
+    fn from(value: i32) -> Self {
+        value as f32
+    }
+}
+
+impl MyFrom<&str> for Option<f32> {
+    fn from(value: &str) -> Self {
+        match value.parse::<f32>() {
+            Ok(value) if value.is_finite() => Some(value),
+            _ => None,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum InputLineUnit {
-    Celcius,
-    Porcentage,
+    Second,
+    None,
 }
 
 pub fn input_line<'a, V, F>(
@@ -51,7 +83,10 @@ where
 {
     // `map_value` is moved in `on_input` so we procuce buttons messages before
     let plus_message = if range.end() > value {
-        let new_value = value.clone() + MyFrom::from(1);
+        let mut new_value = value.clone() + MyFrom::from(1);
+        if &new_value > range.end() {
+            new_value = range.end().clone();
+        }
         let new_cached_value = new_value.to_string();
         Some(map_value(new_value, new_cached_value))
     } else {
@@ -59,7 +94,10 @@ where
     };
 
     let sub_message = if range.start() < value {
-        let new_value = value.clone() - MyFrom::from(1);
+        let mut new_value = value.clone() - MyFrom::from(1);
+        if &new_value < range.start() {
+            new_value = range.start().clone();
+        }
         let new_cached_value = new_value.to_string();
         Some(map_value(new_value, new_cached_value))
     } else {
@@ -90,10 +128,69 @@ where
     }
 
     let unit_text = match unit {
-        InputLineUnit::Celcius => " °C",
-        InputLineUnit::Porcentage => " %",
+        InputLineUnit::Second => " s",
+        InputLineUnit::None => "",
     };
 
+    line(info, input, unit_text.to_owned(), plus_message, sub_message)
+}
+
+/// Like `input_line`, for a value stored in °C or %, but typed
+/// and displayed in the units of the settings.
+pub fn unit_input_line<'a, F>(
+    info: String,
+    value: u8,
+    cached_value: &'a str,
+    kind: ValueKind,
+    units: Units,
+    range: RangeInclusive<u8>,
+    map_value: F,
+) -> Element<'a, ModifNodeMsg>
+where
+    F: 'a + Fn(u8, String) -> ModifNodeMsg,
+{
+    let (start, end) = (*range.start(), *range.end());
+
+    // the typed value, in the unit of the config
+    let parse = move |s: &str| {
+        let displayed = s.trim().parse::<f32>().ok().filter(|v| v.is_finite())?;
+        let value = units.config_value(displayed, &kind).round();
+
+        if value < start.into() || value > end.into() {
+            return None;
+        }
+        Some(value as u8)
+    };
+
+    // one step of the config unit, the display can jump by more than one
+    let step_message = |new_value: u8| {
+        let new_cached_value = units.display_text(new_value.into(), &kind);
+        map_value(new_value, new_cached_value)
+    };
+
+    let plus_message = (value < end).then(|| step_message(value + 1));
+    let sub_message = (value > start).then(|| step_message(value - 1));
+
+    let mut input = TextInput::new(fl!("value"), cached_value)
+        .on_input(move |s| map_value(parse(&s).unwrap_or(value), s))
+        .width(Length::Fixed(45.0));
+
+    if parse(cached_value) != Some(value) {
+        input = input.error("this value is invalid");
+    }
+
+    let unit_text = format!(" {}", units.symbol(&kind));
+
+    line(info, input, unit_text, plus_message, sub_message)
+}
+
+fn line<'a>(
+    info: String,
+    input: TextInput<'a, ModifNodeMsg>,
+    unit_text: String,
+    plus_message: Option<ModifNodeMsg>,
+    sub_message: Option<ModifNodeMsg>,
+) -> Element<'a, ModifNodeMsg> {
     let icon_lenght = Length::Fixed(30.0);
 
     Row::new()
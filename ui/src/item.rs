@@ -15,30 +15,40 @@ use data::{
     config::{
         control::Control,
         custom_temp::{CustomTemp, CustomTempKind},
+        delta::Delta,
+        expression::Expression,
+        filter::{Filter, FilterKind},
         flat::Flat,
         linear::Linear,
+        mix::{Mix, MixKind},
+        pid::Pid,
+        schedule::Schedule,
+        switch::Switch,
         target::Target,
     },
     node::{Input, Node, NodeTypeLight, ValueKind},
+    units::Units,
 };
-use hardware::{HItem, Hardware};
+use hardware::{dry_run::DryRunWrite, HItem, Hardware, HardwareBridge};
 
 use crate::{
     graph::graph_view,
     icon::{icon_button, icon_path_for_node_type, my_icon},
-    input_line::{input_line, InputLineUnit},
+    input_line::{input_line, unit_input_line, InputLineUnit},
     message::{
-        AppMsg, ControlMsg, CustomTempMsg, FlatMsg, LinearMsg, ModifNodeMsg, TargetMsg, ToogleMsg,
+        AppMsg, ControlMsg, CustomTempMsg, DeltaMsg, ExpressionMsg, FilterMsg, FlatMsg, LinearMsg,
+        MixMsg, ModifNodeMsg, PidMsg, ScheduleMsg, SwitchMsg, TargetMsg, ToogleMsg,
     },
     my_widgets::{self, drop_down::DropDown, offset::Offset},
-    node_cache::{LinearC, NodeC, NodesC, TargetC},
-    pick_list_utils::{self, MyOption},
+    node_cache::{FilterC, LinearC, NodeC, NodesC, PidC, ScheduleC, SwitchC, TargetC},
+    pick_list_utils::{self, hardware::HardwarePickListOption, MyOption},
 };
 
-pub fn items_view<'a>(
+pub fn items_view<'a, H: HardwareBridge>(
     nodes: &'a Nodes,
     nodes_c: &'a NodesC,
-    hardware: &'a Hardware,
+    bridge: &'a H,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let mut controls = Vec::new();
     let mut behaviors = Vec::new();
@@ -48,17 +58,24 @@ pub fn items_view<'a>(
 
     for node in nodes.values() {
         let node_c = nodes_c.get(&node.id);
-        let content = item_view(node, node_c, nodes, hardware);
+        let content = item_view(node, node_c, nodes, bridge, units);
 
         match node.node_type.to_light() {
             NodeTypeLight::Control => controls.push(content),
             NodeTypeLight::Fan => fans.push(content),
-            NodeTypeLight::Temp => temps.push(content),
+            NodeTypeLight::Temp | NodeTypeLight::Metric => temps.push(content),
             NodeTypeLight::Graph
             | NodeTypeLight::Flat
             | NodeTypeLight::Linear
-            | NodeTypeLight::Target => behaviors.push(content),
-            NodeTypeLight::CustomTemp => custom_temps.push(content),
+            | NodeTypeLight::Target
+            | NodeTypeLight::Pid
+            | NodeTypeLight::Mix
+            | NodeTypeLight::Switch
+            | NodeTypeLight::Schedule => behaviors.push(content),
+            NodeTypeLight::CustomTemp
+            | NodeTypeLight::Filter
+            | NodeTypeLight::Delta
+            | NodeTypeLight::Expression => custom_temps.push(content),
         }
     }
 
@@ -101,12 +118,14 @@ fn list_view(elements: Vec<Element<AppMsg>>) -> Element<AppMsg> {
         .into()
 }
 
-fn item_view<'a>(
+fn item_view<'a, H: HardwareBridge>(
     node: &'a Node,
     node_c: &'a NodeC,
     nodes: &'a Nodes,
-    hardware: &'a Hardware,
+    bridge: &'a H,
+    units: Units,
 ) -> Element<'a, AppMsg> {
+    let hardware = bridge.hardware();
     let item_icon = my_icon(icon_path_for_node_type(&node.node_type.to_light()));
 
     let mut name = TextInput::new(fl!("name"), &node_c.name)
@@ -149,20 +168,68 @@ fn item_view<'a>(
         .align_items(Alignment::Center);
 
     let node_specific_content = match &node.node_type {
-        data::node::NodeType::Control(control) => control_view(node, control, nodes, hardware),
-        data::node::NodeType::Fan(_fan) => fan_view(node, hardware),
-        data::node::NodeType::Temp(_temp) => temp_view(node, hardware),
-        data::node::NodeType::CustomTemp(custom_temp) => custom_temp_view(node, custom_temp, nodes),
-        data::node::NodeType::Graph(graph) => {
-            graph_view(node, graph, node_c.node_type_c.unwrap_graph_ref(), nodes)
+        data::node::NodeType::Control(control) => {
+            let written = control
+                .control_h
+                .as_ref()
+                .and_then(|control_h| bridge.dry_run_write(control_h));
+            control_view(node, control, nodes, hardware, written, units)
         }
-        data::node::NodeType::Flat(flat) => flat_view(node, flat),
-        data::node::NodeType::Linear(linear) => {
-            linear_view(node, linear, node_c.node_type_c.unwrap_linear_ref(), nodes)
+        data::node::NodeType::Fan(_fan) => fan_view(node, hardware, units),
+        data::node::NodeType::Temp(_temp) => temp_view(node, hardware, units),
+        data::node::NodeType::Metric(_metric) => metric_view(node, hardware, units),
+        data::node::NodeType::CustomTemp(custom_temp) => {
+            custom_temp_view(node, custom_temp, nodes, units)
         }
-        data::node::NodeType::Target(target) => {
-            target_view(node, target, node_c.node_type_c.unwrap_target_ref(), nodes)
+        data::node::NodeType::Graph(graph) => graph_view(
+            node,
+            graph,
+            node_c.node_type_c.unwrap_graph_ref(),
+            nodes,
+            units,
+        ),
+        data::node::NodeType::Flat(flat) => flat_view(node, flat, units),
+        data::node::NodeType::Linear(linear) => linear_view(
+            node,
+            linear,
+            node_c.node_type_c.unwrap_linear_ref(),
+            nodes,
+            units,
+        ),
+        data::node::NodeType::Target(target) => target_view(
+            node,
+            target,
+            node_c.node_type_c.unwrap_target_ref(),
+            nodes,
+            units,
+        ),
+        data::node::NodeType::Pid(pid) => {
+            pid_view(node, pid, node_c.node_type_c.unwrap_pid_ref(), nodes, units)
         }
+        data::node::NodeType::Filter(filter) => filter_view(
+            node,
+            filter,
+            node_c.node_type_c.unwrap_filter_ref(),
+            nodes,
+            units,
+        ),
+        data::node::NodeType::Delta(delta) => delta_view(node, delta, nodes, units),
+        data::node::NodeType::Expression(expression) => expression_view(node, expression, units),
+        data::node::NodeType::Mix(mix) => mix_view(node, mix, nodes, units),
+        data::node::NodeType::Switch(switch) => switch_view(
+            node,
+            switch,
+            node_c.node_type_c.unwrap_switch_ref(),
+            nodes,
+            units,
+        ),
+        data::node::NodeType::Schedule(schedule) => schedule_view(
+            node,
+            schedule,
+            node_c.node_type_c.unwrap_schedule_ref(),
+            nodes,
+            units,
+        ),
     };
 
     let content = Column::new()
@@ -199,11 +266,61 @@ fn pick_hardware<'a, H: HItem>(
     .into()
 }
 
+/// Additional controls driven by the same node
+fn pick_members<'a>(
+    node: &'a Node,
+    control: &'a Control,
+    hardware: &'a Hardware,
+) -> Element<'a, AppMsg> {
+    let members = control.members.iter().map(|member| {
+        let name = match &member.control_h {
+            Some(control_h) => control_h.name.clone(),
+            None => member.hardware_id.clone(),
+        };
+
+        Row::new()
+            .push(Text::new(name).width(Length::Fixed(100.0)))
+            .push(Space::new(Length::Fill, Length::Fixed(0.0)))
+            .push(
+                icon_button("close/20").on_press(
+                    ModifNodeMsg::Control(ControlMsg::RemoveMember(member.hardware_id.clone()))
+                        .to_app(node.id),
+                ),
+            )
+            .align_items(Alignment::Center)
+            .into()
+    });
+
+    // we leverage rc to know if this specific hardware
+    // is already in use by one node
+    let member_options: Vec<HardwarePickListOption> = hardware
+        .controls
+        .iter()
+        .filter(|h| Rc::strong_count(h) == 1)
+        .map(|h| h.into())
+        .collect();
+
+    let current_member = HardwarePickListOption {
+        name: fl!("add_member"),
+        id: Default::default(),
+    };
+
+    let pick_member = PickList::new(member_options, Some(current_member), |selected| {
+        ModifNodeMsg::Control(ControlMsg::AddMember(selected.id)).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    Column::with_children(vec![pick_member, Column::with_children(members).into()]).into()
+}
+
 fn control_view<'a>(
     node: &'a Node,
     control: &'a Control,
     nodes: &'a Nodes,
     hardware: &'a Hardware,
+    written: Option<DryRunWrite>,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let input_options =
         pick_list_utils::input::optional_availlable_inputs(nodes, node, control.input.is_some());
@@ -215,11 +332,12 @@ fn control_view<'a>(
     .width(Length::Fill)
     .into();
 
-    let content = vec![
+    let mut content = vec![
         pick_hardware(node, &hardware.controls, true),
+        pick_members(node, control, hardware),
         pick_input,
         Row::new()
-            .push(Text::new(node.value_text(&ValueKind::Porcentage)))
+            .push(Text::new(node.value_text(&ValueKind::Porcentage, &units)))
             .push(Space::new(Length::Fill, Length::Fixed(0.0)))
             .push(Toggler::new(None, control.active, |is_active| {
                 ModifNodeMsg::Control(ControlMsg::Active(is_active)).to_app(node.id)
@@ -229,22 +347,39 @@ fn control_view<'a>(
             .into(),
     ];
 
+    if let Some(written) = written {
+        let value = match written {
+            DryRunWrite::Value(value) => units.value_text(value, &ValueKind::Porcentage),
+            DryRunWrite::RpmTarget(rpm) => units.value_text(rpm, &ValueKind::RPM),
+        };
+        content.push(Text::new(fl!("dry_run_write", value = value)).into());
+    }
+
     Column::with_children(content).into()
 }
 
-fn fan_view<'a>(node: &'a Node, hardware: &'a Hardware) -> Element<'a, AppMsg> {
+fn fan_view<'a>(node: &'a Node, hardware: &'a Hardware, units: Units) -> Element<'a, AppMsg> {
     let content = vec![
         pick_hardware(node, &hardware.fans, false),
-        Text::new(node.value_text(&ValueKind::RPM)).into(),
+        Text::new(node.value_text(&ValueKind::RPM, &units)).into(),
     ];
 
     Column::with_children(content).into()
 }
 
-fn temp_view<'a>(node: &'a Node, hardware: &'a Hardware) -> Element<'a, AppMsg> {
+fn temp_view<'a>(node: &'a Node, hardware: &'a Hardware, units: Units) -> Element<'a, AppMsg> {
     let content = vec![
         pick_hardware(node, &hardware.temps, false),
-        Text::new(node.value_text(&ValueKind::Celsius)).into(),
+        Text::new(node.value_text(&ValueKind::Celsius, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn metric_view<'a>(node: &'a Node, hardware: &'a Hardware, units: Units) -> Element<'a, AppMsg> {
+    let content = vec![
+        pick_hardware(node, &hardware.metrics, false),
+        Text::new(node.value_text(&ValueKind::Load, &units)).into(),
     ];
 
     Column::with_children(content).into()
@@ -254,6 +389,7 @@ fn custom_temp_view<'a>(
     node: &'a Node,
     custom_temp: &'a CustomTemp,
     nodes: &'a Nodes,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let kind_options = CustomTempKind::VALUES
         .iter()
@@ -297,13 +433,343 @@ fn custom_temp_view<'a>(
         pick_kind,
         pick_input,
         Column::with_children(inputs).into(),
-        Text::new(node.value_text(&ValueKind::Celsius)).into(),
+        Text::new(node.value_text(&ValueKind::Celsius, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn filter_view<'a>(
+    node: &'a Node,
+    filter: &'a Filter,
+    filter_c: &'a FilterC,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    let kind_options = FilterKind::VALUES
+        .iter()
+        .filter(|k| &filter.kind != *k)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let pick_kind = PickList::new(kind_options, Some(filter.kind.clone()), |k| {
+        ModifNodeMsg::Filter(FilterMsg::Kind(k)).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    let input_options =
+        pick_list_utils::input::optional_availlable_inputs(nodes, node, filter.input.is_some());
+    let current_input: MyOption<Input> = filter.input.clone().into();
+    let pick_input = PickList::new(input_options, Some(current_input), |input| {
+        ModifNodeMsg::ReplaceInput(input.into()).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    let content = vec![
+        pick_kind,
+        pick_input,
+        input_line(
+            fl!("span"),
+            &filter.span,
+            &filter_c.span,
+            InputLineUnit::Second,
+            &(1..=3600),
+            |val, cached_val| ModifNodeMsg::Filter(FilterMsg::Span(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        Text::new(node.value_text(&ValueKind::Celsius, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn delta_view<'a>(
+    node: &'a Node,
+    delta: &'a Delta,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    fn slot_view<'a>(
+        node: &'a Node,
+        nodes: &'a Nodes,
+        label: String,
+        slot: &'a Option<String>,
+        message: fn(Option<Input>) -> DeltaMsg,
+    ) -> Element<'a, AppMsg> {
+        let input_options =
+            pick_list_utils::input::optional_availlable_inputs(nodes, node, slot.is_some());
+        let current_input: MyOption<Input> = slot.clone().into();
+        let pick_input = PickList::new(input_options, Some(current_input), move |input| {
+            ModifNodeMsg::Delta(message(input.into())).to_app(node.id)
+        })
+        .width(Length::Fill);
+
+        Column::new().push(Text::new(label)).push(pick_input).into()
+    }
+
+    let content = vec![
+        slot_view(
+            node,
+            nodes,
+            fl!("delta_input"),
+            &delta.input,
+            DeltaMsg::Input,
+        ),
+        slot_view(
+            node,
+            nodes,
+            fl!("delta_reference"),
+            &delta.reference,
+            DeltaMsg::Reference,
+        ),
+        Text::new(node.value_text(&ValueKind::TempDelta, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn expression_view<'a>(
+    node: &'a Node,
+    expression: &'a Expression,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    let mut input = TextInput::new(fl!("expression"), &expression.expression)
+        .on_input(|s| ModifNodeMsg::Expression(ExpressionMsg::Expression(s)).to_app(node.id))
+        .width(Length::Fill);
+
+    if let Some(e) = expression.error() {
+        input = input.error(e.to_string());
+    }
+
+    let inputs = node
+        .inputs
+        .iter()
+        .map(|input| Text::new(input.name.clone()).into());
+
+    let content = vec![
+        input.into(),
+        Column::with_children(inputs).into(),
+        Text::new(node.value_text(&ValueKind::Unitless, &units)).into(),
     ];
 
     Column::with_children(content).into()
 }
 
-fn flat_view<'a>(node: &'a Node, flat: &'a Flat) -> Element<'a, AppMsg> {
+fn mix_view<'a>(
+    node: &'a Node,
+    mix: &'a Mix,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    let kind_options = MixKind::VALUES
+        .iter()
+        .filter(|k| &mix.kind != *k)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let pick_kind = PickList::new(kind_options, Some(mix.kind.clone()), |k| {
+        ModifNodeMsg::Mix(MixMsg::Kind(k)).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    let inputs = node.inputs.iter().map(|input| {
+        Row::new()
+            .push(Text::new(input.name.clone()).width(Length::Fixed(100.0)))
+            .push(Space::new(Length::Fill, Length::Fixed(0.0)))
+            .push(
+                icon_button("close/20")
+                    .on_press(ModifNodeMsg::RemoveInput(input.clone()).to_app(node.id)),
+            )
+            .align_items(Alignment::Center)
+            .into()
+    });
+
+    let input_options: Vec<Input> =
+        pick_list_utils::input::availlable_inputs(nodes, node).collect();
+
+    let current_input = Input {
+        id: Default::default(),
+        name: fl!("behavior_selection"),
+    };
+
+    let pick_input = PickList::new(input_options, Some(current_input), |input| {
+        ModifNodeMsg::AddInput(input).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    let content = vec![
+        pick_kind,
+        pick_input,
+        Column::with_children(inputs).into(),
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn switch_view<'a>(
+    node: &'a Node,
+    switch: &'a Switch,
+    switch_c: &'a SwitchC,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    fn slot_view<'a>(
+        node: &'a Node,
+        nodes: &'a Nodes,
+        label: String,
+        slot: usize,
+        input: &'a Option<String>,
+        message: fn(Option<Input>) -> SwitchMsg,
+    ) -> Element<'a, AppMsg> {
+        let input_options = pick_list_utils::input::optional_slot_inputs(nodes, node, slot);
+        let current_input: MyOption<Input> = input.clone().into();
+        let pick_input = PickList::new(input_options, Some(current_input), move |input| {
+            ModifNodeMsg::Switch(message(input.into())).to_app(node.id)
+        })
+        .width(Length::Fill);
+
+        Column::new().push(Text::new(label)).push(pick_input).into()
+    }
+
+    let content = vec![
+        slot_view(
+            node,
+            nodes,
+            fl!("switch_condition"),
+            Switch::CONDITION_SLOT,
+            &switch.condition,
+            SwitchMsg::Condition,
+        ),
+        unit_input_line(
+            fl!("switch_threshold"),
+            switch.threshold,
+            &switch_c.threshold,
+            ValueKind::Celsius,
+            units,
+            0..=255,
+            |val, cached_val| ModifNodeMsg::Switch(SwitchMsg::Threshold(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        unit_input_line(
+            fl!("switch_hysteresis"),
+            switch.hysteresis,
+            &switch_c.hysteresis,
+            ValueKind::TempDelta,
+            units,
+            0..=255,
+            |val, cached_val| ModifNodeMsg::Switch(SwitchMsg::Hysteresis(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        slot_view(
+            node,
+            nodes,
+            fl!("switch_above"),
+            1,
+            &switch.input_above,
+            SwitchMsg::InputAbove,
+        ),
+        slot_view(
+            node,
+            nodes,
+            fl!("switch_below"),
+            2,
+            &switch.input_below,
+            SwitchMsg::InputBelow,
+        ),
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
+    ];
+
+    Column::with_children(content).into()
+}
+
+fn schedule_view<'a>(
+    node: &'a Node,
+    schedule: &'a Schedule,
+    schedule_c: &'a ScheduleC,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    fn slot_view<'a>(
+        node: &'a Node,
+        nodes: &'a Nodes,
+        label: String,
+        slot: usize,
+        input: &'a Option<String>,
+        message: impl Fn(Option<Input>) -> ScheduleMsg + 'a,
+    ) -> Element<'a, AppMsg> {
+        let input_options = pick_list_utils::input::optional_slot_inputs(nodes, node, slot);
+        let current_input: MyOption<Input> = input.clone().into();
+        let pick_input = PickList::new(input_options, Some(current_input), move |input| {
+            ModifNodeMsg::Schedule(message(input.into())).to_app(node.id)
+        })
+        .width(Length::Fill);
+
+        Column::new().push(Text::new(label)).push(pick_input).into()
+    }
+
+    let mut content = vec![
+        slot_view(
+            node,
+            nodes,
+            fl!("schedule_default"),
+            0,
+            &schedule.input,
+            ScheduleMsg::Input,
+        ),
+        slot_view(
+            node,
+            nodes,
+            fl!("schedule_override_condition"),
+            Schedule::OVERRIDE_SLOT,
+            &schedule.override_condition,
+            ScheduleMsg::OverrideCondition,
+        ),
+        unit_input_line(
+            fl!("schedule_override_threshold"),
+            schedule.override_threshold,
+            &schedule_c.override_threshold,
+            ValueKind::Celsius,
+            units,
+            0..=255,
+            |val, cached_val| {
+                ModifNodeMsg::Schedule(ScheduleMsg::OverrideThreshold(val, cached_val))
+            },
+        )
+        .map(|m| m.to_app(node.id)),
+    ];
+
+    for (index, period) in schedule.periods.iter().enumerate() {
+        let mut label = format!("{} - {}", period.start, period.end);
+        for day in &period.days {
+            label.push(' ');
+            label.push_str(&day.to_string());
+        }
+        if let Some(max_speed) = period.max_speed {
+            label.push_str(&format!(" (max {max_speed} %)"));
+        }
+
+        let slot = Schedule::OVERRIDE_SLOT + 1 + index;
+        content.push(slot_view(
+            node,
+            nodes,
+            label,
+            slot,
+            &period.input,
+            move |input| ScheduleMsg::PeriodInput(index, input),
+        ));
+    }
+
+    content.push(Text::new(node.value_text(&ValueKind::Porcentage, &units)).into());
+
+    Column::with_children(content).into()
+}
+
+fn flat_view<'a>(node: &'a Node, flat: &'a Flat, units: Units) -> Element<'a, AppMsg> {
     let mut sub_button = icon_button("remove/24");
     if flat.value > 0 {
         sub_button =
@@ -322,7 +788,7 @@ fn flat_view<'a>(node: &'a Node, flat: &'a Flat) -> Element<'a, AppMsg> {
         .align_items(Alignment::Center);
 
     let buttons = Row::new()
-        .push(Text::new(node.value_text(&ValueKind::Porcentage)))
+        .push(Text::new(node.value_text(&ValueKind::Porcentage, &units)))
         .push(Space::new(Length::Fill, Length::Fixed(0.0)))
         .push(buttons)
         .align_items(Alignment::Center)
@@ -343,6 +809,7 @@ fn linear_view<'a>(
     linear: &'a Linear,
     linear_c: &'a LinearC,
     nodes: &'a Nodes,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let input_options =
         pick_list_utils::input::optional_availlable_inputs(nodes, node, linear.input.is_some());
@@ -355,40 +822,44 @@ fn linear_view<'a>(
 
     let content = vec![
         pick_input,
-        Text::new(node.value_text(&ValueKind::Porcentage)).into(),
-        input_line(
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
+        unit_input_line(
             fl!("min_temp"),
-            &linear.min_temp,
+            linear.min_temp,
             &linear_c.min_temp,
-            InputLineUnit::Celcius,
-            &(0..=255),
+            ValueKind::Celsius,
+            units,
+            0..=255,
             |val, cached_val| ModifNodeMsg::Linear(LinearMsg::MinTemp(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("min_speed"),
-            &linear.min_speed,
+            linear.min_speed,
             &linear_c.min_speed,
-            InputLineUnit::Porcentage,
-            &(0..=100),
+            ValueKind::Porcentage,
+            units,
+            0..=100,
             |val, cached_val| ModifNodeMsg::Linear(LinearMsg::MinSpeed(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("max_temp"),
-            &linear.max_temp,
+            linear.max_temp,
             &linear_c.max_temp,
-            InputLineUnit::Celcius,
-            &(0..=255),
+            ValueKind::Celsius,
+            units,
+            0..=255,
             |val, cached_val| ModifNodeMsg::Linear(LinearMsg::MaxTemp(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("max_speed"),
-            &linear.max_speed,
+            linear.max_speed,
             &linear_c.max_speed,
-            InputLineUnit::Porcentage,
-            &(0..=100),
+            ValueKind::Porcentage,
+            units,
+            0..=100,
             |val, cached_val| ModifNodeMsg::Linear(LinearMsg::MaxSpeed(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
@@ -402,6 +873,7 @@ fn target_view<'a>(
     target: &'a Target,
     target_c: &'a TargetC,
     nodes: &'a Nodes,
+    units: Units,
 ) -> Element<'a, AppMsg> {
     let input_options =
         pick_list_utils::input::optional_availlable_inputs(nodes, node, target.input.is_some());
@@ -414,40 +886,44 @@ fn target_view<'a>(
 
     let content = vec![
         pick_input,
-        Text::new(node.value_text(&ValueKind::Porcentage)).into(),
-        input_line(
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
+        unit_input_line(
             fl!("idle_temp"),
-            &target.idle_temp,
+            target.idle_temp,
             &target_c.idle_temp,
-            InputLineUnit::Celcius,
-            &(0..=255),
+            ValueKind::Celsius,
+            units,
+            0..=255,
             |val, cached_val| ModifNodeMsg::Target(TargetMsg::IdleTemp(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("idle_speed"),
-            &target.idle_speed,
+            target.idle_speed,
             &target_c.idle_speed,
-            InputLineUnit::Porcentage,
-            &(0..=100),
+            ValueKind::Porcentage,
+            units,
+            0..=100,
             |val, cached_val| ModifNodeMsg::Target(TargetMsg::IdleSpeed(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("load_temp"),
-            &target.load_temp,
+            target.load_temp,
             &target_c.load_temp,
-            InputLineUnit::Celcius,
-            &(0..=255),
+            ValueKind::Celsius,
+            units,
+            0..=255,
             |val, cached_val| ModifNodeMsg::Target(TargetMsg::LoadTemp(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
-        input_line(
+        unit_input_line(
             fl!("load_speed"),
-            &target.load_speed,
+            target.load_speed,
             &target_c.load_speed,
-            InputLineUnit::Porcentage,
-            &(0..=100),
+            ValueKind::Porcentage,
+            units,
+            0..=100,
             |val, cached_val| ModifNodeMsg::Target(TargetMsg::LoadSpeed(val, cached_val)),
         )
         .map(|m| m.to_app(node.id)),
@@ -455,3 +931,93 @@ fn target_view<'a>(
 
     Column::with_children(content).into()
 }
+
+fn pid_view<'a>(
+    node: &'a Node,
+    pid: &'a Pid,
+    pid_c: &'a PidC,
+    nodes: &'a Nodes,
+    units: Units,
+) -> Element<'a, AppMsg> {
+    let input_options =
+        pick_list_utils::input::optional_availlable_inputs(nodes, node, pid.input.is_some());
+    let current_input: MyOption<Input> = pid.input.clone().into();
+    let pick_input = PickList::new(input_options, Some(current_input), |input| {
+        ModifNodeMsg::ReplaceInput(input.into()).to_app(node.id)
+    })
+    .width(Length::Fill)
+    .into();
+
+    let content = vec![
+        pick_input,
+        Text::new(node.value_text(&ValueKind::Porcentage, &units)).into(),
+        unit_input_line(
+            fl!("setpoint"),
+            pid.setpoint,
+            &pid_c.setpoint,
+            ValueKind::Celsius,
+            units,
+            0..=255,
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::Setpoint(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        input_line(
+            fl!("kp"),
+            &pid.kp,
+            &pid_c.kp,
+            InputLineUnit::None,
+            &(0.0..=1000.0),
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::Kp(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        input_line(
+            fl!("ki"),
+            &pid.ki,
+            &pid_c.ki,
+            InputLineUnit::None,
+            &(0.0..=1000.0),
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::Ki(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        input_line(
+            fl!("kd"),
+            &pid.kd,
+            &pid_c.kd,
+            InputLineUnit::None,
+            &(0.0..=1000.0),
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::Kd(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        unit_input_line(
+            fl!("min_speed"),
+            pid.min_speed,
+            &pid_c.min_speed,
+            ValueKind::Porcentage,
+            units,
+            0..=100,
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::MinSpeed(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        unit_input_line(
+            fl!("max_speed"),
+            pid.max_speed,
+            &pid_c.max_speed,
+            ValueKind::Porcentage,
+            units,
+            0..=100,
+            |val, cached_val| ModifNodeMsg::Pid(PidMsg::MaxSpeed(val, cached_val)),
+        )
+        .map(|m| m.to_app(node.id)),
+        Row::new()
+            .push(Text::new(fl!("anti_windup")))
+            .push(Space::new(Length::Fill, Length::Fixed(0.0)))
+            .push(Toggler::new(None, pid.anti_windup, |anti_windup| {
+                ModifNodeMsg::Pid(PidMsg::AntiWindup(anti_windup)).to_app(node.id)
+            }))
+            .align_items(Alignment::Center)
+            .width(Length::Fill)
+            .into(),
+    ];
+
+    Column::with_children(content).into()
+}
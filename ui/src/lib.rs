@@ -1,7 +1,6 @@
 use std::time::Duration;
 
 use data::{
-    app_graph::AppGraph,
     config::Config,
     node::{validate_name, IsValid, NodeType},
     settings::AppTheme,
@@ -27,7 +26,10 @@ use cosmic::{
     ApplicationExt, Element,
 };
 
-use crate::message::{AppMsg, ControlMsg, CustomTempMsg, FlatMsg, LinearMsg, TargetMsg};
+use crate::message::{
+    AppMsg, ControlMsg, CustomTempMsg, DeltaMsg, ExpressionMsg, FilterMsg, FlatMsg, LinearMsg,
+    MixMsg, PidMsg, ScheduleMsg, SwitchMsg, TargetMsg,
+};
 
 use crate::add_node::add_node_button_view;
 
@@ -90,7 +92,10 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
             .to_owned();
 
         let mut ui_state = Ui {
-            nodes_c: NodesC::new(flags.app_graph.nodes.values()),
+            nodes_c: NodesC::new(
+                flags.app_graph.nodes.values(),
+                flags.dir_manager.settings().units(),
+            ),
             app_state: flags,
             core,
             create_button_expanded: false,
@@ -116,10 +121,20 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
         let dir_manager = &mut self.app_state.dir_manager;
 
         match message {
-            AppMsg::Tick => return self.maybe_update_hardware_to_update_graph(),
+            AppMsg::Tick => {
+                if let Some(config_name) = self.app_state.auto_switch_config() {
+                    self.current_config_cached = config_name;
+                    self.nodes_c = NodesC::new(
+                        self.app_state.app_graph.nodes.values(),
+                        self.app_state.dir_manager.settings().units(),
+                    );
+                }
+                return self.maybe_update_hardware_to_update_graph();
+            }
             AppMsg::UpdateGraph => {
                 if let Err(e) = self.app_state.update.all(
                     &mut self.app_state.app_graph.nodes,
+                    self.app_state.app_graph.emergency.as_ref(),
                     &mut self.app_state.bridge,
                 ) {
                     error!("{}", e);
@@ -193,6 +208,19 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                                     None => None,
                                 }
                             }
+                            NodeType::Metric(i) => {
+                                i.hardware_id = hardware_id;
+                                i.metric_h = match &i.hardware_id {
+                                    Some(hardware_id) => bridge
+                                        .hardware()
+                                        .metrics
+                                        .iter()
+                                        .find(|h| &h.hardware_id == hardware_id)
+                                        .cloned(),
+
+                                    None => None,
+                                }
+                            }
                             _ => panic!("node have no hardware id"),
                         }
                     }
@@ -212,6 +240,8 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             NodeType::Graph(i) => i.input = optional_name,
                             NodeType::Linear(i, ..) => i.input = optional_name,
                             NodeType::Target(i, ..) => i.input = optional_name,
+                            NodeType::Pid(i) => i.input = optional_name,
+                            NodeType::Filter(i) => i.input = optional_name,
                             _ => panic!("node have not exactly one input"),
                         }
                     }
@@ -220,6 +250,7 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
 
                         match &mut node.node_type {
                             NodeType::CustomTemp(i) => i.inputs.push(input.name),
+                            NodeType::Mix(i) => i.inputs.push(input.name),
                             _ => panic!("node have not multiple inputs"),
                         }
                     }
@@ -230,6 +261,9 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             NodeType::CustomTemp(i) => {
                                 i.inputs.remove_elem(|n| n == &input.name);
                             }
+                            NodeType::Mix(i) => {
+                                i.inputs.remove_elem(|n| n == &input.name);
+                            }
                             _ => panic!("node have not multiple inputs"),
                         }
                     }
@@ -238,6 +272,29 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             let control = node.node_type.unwrap_control_mut();
                             control.active = is_active;
                         }
+                        ControlMsg::AddMember(hardware_id) => {
+                            let control_h = self
+                                .app_state
+                                .bridge
+                                .hardware()
+                                .controls
+                                .iter()
+                                .find(|h| h.hardware_id == hardware_id)
+                                .cloned();
+
+                            if let Some(control_h) = control_h {
+                                let control = node.node_type.unwrap_control_mut();
+                                control.add_member(control_h);
+                            }
+                        }
+                        ControlMsg::RemoveMember(hardware_id) => {
+                            let control = node.node_type.unwrap_control_mut();
+                            if let Err(e) =
+                                control.remove_member(&hardware_id, &mut self.app_state.bridge)
+                            {
+                                error!("Can't set member to auto when removing it: {e}.");
+                            }
+                        }
                     },
                     ModifNodeMsg::CustomTemp(custom_temp_msg) => match custom_temp_msg {
                         CustomTempMsg::Kind(kind) => {
@@ -245,6 +302,12 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             custom_temp.kind = kind;
                         }
                     },
+                    ModifNodeMsg::Mix(mix_msg) => match mix_msg {
+                        MixMsg::Kind(kind) => {
+                            let mix = node.node_type.unwrap_mix_mut();
+                            mix.kind = kind;
+                        }
+                    },
                     ModifNodeMsg::Flat(flat_msg) => match flat_msg {
                         FlatMsg::Value(value) => {
                             let flat = node.node_type.unwrap_flat_mut();
@@ -298,6 +361,143 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             }
                         }
                     }
+                    ModifNodeMsg::Pid(pid_msg) => {
+                        let pid = node.node_type.unwrap_pid_mut();
+                        let pid_c = self.nodes_c.get_mut(&id).node_type_c.unwrap_pid_mut();
+
+                        match pid_msg {
+                            PidMsg::Setpoint(setpoint, cached_value) => {
+                                pid.setpoint = setpoint;
+                                pid_c.setpoint = cached_value;
+                            }
+                            PidMsg::Kp(kp, cached_value) => {
+                                pid.kp = kp;
+                                pid_c.kp = cached_value;
+                            }
+                            PidMsg::Ki(ki, cached_value) => {
+                                pid.ki = ki;
+                                pid_c.ki = cached_value;
+                            }
+                            PidMsg::Kd(kd, cached_value) => {
+                                pid.kd = kd;
+                                pid_c.kd = cached_value;
+                            }
+                            PidMsg::MinSpeed(min_speed, cached_value) => {
+                                pid.min_speed = min_speed;
+                                pid_c.min_speed = cached_value;
+                            }
+                            PidMsg::MaxSpeed(max_speed, cached_value) => {
+                                pid.max_speed = max_speed;
+                                pid_c.max_speed = cached_value;
+                            }
+                            PidMsg::AntiWindup(anti_windup) => {
+                                pid.anti_windup = anti_windup;
+                            }
+                        }
+                    }
+                    ModifNodeMsg::Filter(filter_msg) => {
+                        let filter = node.node_type.unwrap_filter_mut();
+                        let filter_c = self.nodes_c.get_mut(&id).node_type_c.unwrap_filter_mut();
+
+                        match filter_msg {
+                            FilterMsg::Kind(kind) => {
+                                filter.kind = kind;
+                            }
+                            FilterMsg::Span(span, cached_value) => {
+                                filter.span = span;
+                                filter_c.span = cached_value;
+                            }
+                        }
+                    }
+                    ModifNodeMsg::Delta(delta_msg) => {
+                        let delta = node.node_type.unwrap_delta_mut();
+
+                        match delta_msg {
+                            DeltaMsg::Input(input) => delta.input = input.map(|i| i.name),
+                            DeltaMsg::Reference(input) => delta.reference = input.map(|i| i.name),
+                        }
+
+                        // node.inputs must follow the order of the slots
+                        let app_graph = &mut self.app_state.app_graph;
+                        let sanitize = data::node::sanitize_inputs(
+                            app_graph.get(&id),
+                            &app_graph.nodes,
+                            false,
+                        );
+                        app_graph.get_mut(&id).set_inputs(sanitize);
+                    }
+                    ModifNodeMsg::Switch(switch_msg) => {
+                        let switch = node.node_type.unwrap_switch_mut();
+                        let switch_c = self.nodes_c.get_mut(&id).node_type_c.unwrap_switch_mut();
+
+                        match switch_msg {
+                            SwitchMsg::Condition(input) => switch.condition = input.map(|i| i.name),
+                            SwitchMsg::Threshold(threshold, cached_value) => {
+                                switch.threshold = threshold;
+                                switch_c.threshold = cached_value;
+                            }
+                            SwitchMsg::Hysteresis(hysteresis, cached_value) => {
+                                switch.hysteresis = hysteresis;
+                                switch_c.hysteresis = cached_value;
+                            }
+                            SwitchMsg::InputAbove(input) => {
+                                switch.input_above = input.map(|i| i.name)
+                            }
+                            SwitchMsg::InputBelow(input) => {
+                                switch.input_below = input.map(|i| i.name)
+                            }
+                        }
+
+                        let app_graph = &mut self.app_state.app_graph;
+                        let sanitize = data::node::sanitize_inputs(
+                            app_graph.get(&id),
+                            &app_graph.nodes,
+                            false,
+                        );
+                        app_graph.get_mut(&id).set_inputs(sanitize);
+                    }
+                    ModifNodeMsg::Schedule(schedule_msg) => {
+                        let schedule = node.node_type.unwrap_schedule_mut();
+                        let schedule_c =
+                            self.nodes_c.get_mut(&id).node_type_c.unwrap_schedule_mut();
+
+                        match schedule_msg {
+                            ScheduleMsg::Input(input) => schedule.input = input.map(|i| i.name),
+                            ScheduleMsg::PeriodInput(index, input) => {
+                                schedule.periods[index].input = input.map(|i| i.name)
+                            }
+                            ScheduleMsg::OverrideCondition(input) => {
+                                schedule.override_condition = input.map(|i| i.name)
+                            }
+                            ScheduleMsg::OverrideThreshold(threshold, cached_value) => {
+                                schedule.override_threshold = threshold;
+                                schedule_c.override_threshold = cached_value;
+                            }
+                        }
+
+                        let app_graph = &mut self.app_state.app_graph;
+                        let sanitize = data::node::sanitize_inputs(
+                            app_graph.get(&id),
+                            &app_graph.nodes,
+                            false,
+                        );
+                        app_graph.get_mut(&id).set_inputs(sanitize);
+                    }
+                    ModifNodeMsg::Expression(expression_msg) => match expression_msg {
+                        ExpressionMsg::Expression(expression_text) => {
+                            let expression = node.node_type.unwrap_expression_mut();
+                            expression.set_expression(expression_text);
+
+                            // the inputs are the variables of the expression
+                            let app_graph = &mut self.app_state.app_graph;
+                            let sanitize = data::node::sanitize_inputs(
+                                app_graph.get(&id),
+                                &app_graph.nodes,
+                                false,
+                            );
+                            app_graph.get_mut(&id).set_inputs(sanitize);
+                        }
+                    },
                     ModifNodeMsg::Delete => {
                         match self.app_state.app_graph.remove_node(id) {
                             Some(mut node) => {
@@ -334,6 +534,9 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
 
                                 graph.coords.0.insert_sorted(|c| new.cmp(c), new.clone());
                             }
+                            message::GraphMsg::Interpolation(interpolation) => {
+                                graph.interpolation = interpolation;
+                            }
                         }
                     }
                 }
@@ -355,10 +558,28 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                 SettingsMsg::UpdateDelay(update_delay) => dir_manager.update_settings(|settings| {
                     settings.update_delay = update_delay;
                 }),
+                SettingsMsg::TempUnit(temp_unit) => {
+                    dir_manager.update_settings(|settings| {
+                        settings.temp_unit = temp_unit;
+                    });
+                    self.nodes_c.set_units(
+                        self.app_state.app_graph.nodes.values(),
+                        dir_manager.settings().units(),
+                    );
+                }
+                SettingsMsg::SpeedUnit(speed_unit) => {
+                    dir_manager.update_settings(|settings| {
+                        settings.speed_unit = speed_unit;
+                    });
+                    self.nodes_c.set_units(
+                        self.app_state.app_graph.nodes.values(),
+                        dir_manager.settings().units(),
+                    );
+                }
             },
             AppMsg::NewNode(node_type_light) => {
                 let node = self.app_state.app_graph.create_new_node(node_type_light);
-                let node_c = NodeC::new(&node);
+                let node_c = NodeC::new(&node, dir_manager.settings().units());
                 self.nodes_c.insert(node.id, node_c);
                 self.app_state.app_graph.insert_node(node);
             }
@@ -387,21 +608,14 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                 ConfigMsg::Change(selected) => {
                     self.choose_config_expanded = false;
 
-                    if selected.is_some() {
-                        self.app_state.update.set_valid_root_nodes_to_auto(
-                            &mut self.app_state.app_graph.nodes,
-                            &self.app_state.app_graph.root_nodes,
-                            &mut self.app_state.bridge,
-                        );
-                    }
-
-                    match dir_manager.change_config(selected) {
-                        Ok(config) => match config {
-                            Some((config_name, config)) => {
+                    match self.app_state.change_config(selected) {
+                        Ok(config_name) => match config_name {
+                            Some(config_name) => {
                                 self.current_config_cached = config_name;
-                                self.app_state.app_graph =
-                                    AppGraph::from_config(config, self.app_state.bridge.hardware());
-                                self.nodes_c = NodesC::new(self.app_state.app_graph.nodes.values());
+                                self.nodes_c = NodesC::new(
+                                    self.app_state.app_graph.nodes.values(),
+                                    self.app_state.dir_manager.settings().units(),
+                                );
 
                                 return self.maybe_update_hardware_to_update_graph();
                             }
@@ -465,14 +679,20 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
                             node_input.name = name.clone();
                             let mut inputs = n.node_type.get_inputs();
 
-                            match inputs.iter().position(|n| n == &previous_name) {
-                                Some(index) => {
-                                    inputs[index] = name.clone();
-                                    n.node_type.set_inputs(inputs)
-                                }
-                                None => {
-                                    error!("input id found in node inputs but the corresponding name was not found in item input")
-                                }
+                            // the same node can be used in several slots
+                            let mut found = false;
+                            for input in inputs
+                                .iter_mut()
+                                .filter(|n| n.as_deref() == Some(previous_name.as_str()))
+                            {
+                                *input = Some(name.clone());
+                                found = true;
+                            }
+
+                            if found {
+                                n.node_type.set_inputs(inputs)
+                            } else {
+                                error!("input id found in node inputs but the corresponding name was not found in item input")
                             }
                         }
                     }
@@ -539,7 +759,12 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
         let app_state = &self.app_state;
         let app_graph = &app_state.app_graph;
 
-        let content = items_view(&app_graph.nodes, &self.nodes_c, app_state.bridge.hardware());
+        let content = items_view(
+            &app_graph.nodes,
+            &self.nodes_c,
+            &app_state.bridge,
+            app_state.dir_manager.settings().units(),
+        );
 
         let floating_button = Column::new()
             .push(Space::new(0.0, Length::Fill))
@@ -561,7 +786,7 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
     }
 
     fn header_end(&self) -> Vec<Element<Self::Message>> {
-        headers::header_end()
+        headers::header_end(self.app_state.bridge.is_dry_run())
     }
 
     fn context_drawer(&self) -> Option<Element<Self::Message>> {
@@ -591,7 +816,11 @@ impl<H: HardwareBridge + 'static> cosmic::Application for Ui<H> {
     fn view_window(&self, id: window::Id) -> Element<Self::Message> {
         if let Some(graph_window) = &self.graph_window {
             if graph_window.window_id == id {
-                return graph_window_view(graph_window, &self.app_state.app_graph.nodes);
+                return graph_window_view(
+                    graph_window,
+                    &self.app_state.app_graph.nodes,
+                    self.app_state.dir_manager.settings().units(),
+                );
             }
         }
 
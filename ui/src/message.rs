@@ -1,8 +1,14 @@
 use data::{
-    config::{custom_temp::CustomTempKind, graph::Coord},
+    config::{
+        custom_temp::CustomTempKind,
+        filter::FilterKind,
+        graph::{Coord, Interpolation},
+        mix::MixKind,
+    },
     id::Id,
     node::{Input, NodeTypeLight},
     settings::AppTheme,
+    units::{SpeedUnit, TempUnit},
 };
 
 use crate::graph::GraphWindowMsg;
@@ -40,6 +46,8 @@ pub enum ConfigMsg {
 pub enum SettingsMsg {
     Theme(AppTheme),
     UpdateDelay(u64),
+    TempUnit(TempUnit),
+    SpeedUnit(SpeedUnit),
 }
 
 #[derive(Debug, Clone)]
@@ -63,12 +71,21 @@ pub enum ModifNodeMsg {
     Flat(FlatMsg),
     Linear(LinearMsg),
     Target(TargetMsg),
+    Pid(PidMsg),
+    Filter(FilterMsg),
+    Delta(DeltaMsg),
+    Expression(ExpressionMsg),
+    Mix(MixMsg),
+    Switch(SwitchMsg),
+    Schedule(ScheduleMsg),
     Graph(GraphMsg),
 }
 
 #[derive(Debug, Clone)]
 pub enum ControlMsg {
     Active(bool),
+    AddMember(String),
+    RemoveMember(String),
 }
 
 #[derive(Debug, Clone)]
@@ -97,12 +114,63 @@ pub enum TargetMsg {
     LoadSpeed(u8, String),
 }
 
+#[derive(Debug, Clone)]
+pub enum PidMsg {
+    Setpoint(u8, String),
+    Kp(f32, String),
+    Ki(f32, String),
+    Kd(f32, String),
+    MinSpeed(u8, String),
+    MaxSpeed(u8, String),
+    AntiWindup(bool),
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpressionMsg {
+    Expression(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum MixMsg {
+    Kind(MixKind),
+}
+
+#[derive(Debug, Clone)]
+pub enum SwitchMsg {
+    Condition(Option<Input>),
+    Threshold(u8, String),
+    Hysteresis(u8, String),
+    InputAbove(Option<Input>),
+    InputBelow(Option<Input>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ScheduleMsg {
+    Input(Option<Input>),
+    PeriodInput(usize, Option<Input>),
+    OverrideCondition(Option<Input>),
+    OverrideThreshold(u8, String),
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterMsg {
+    Kind(FilterKind),
+    Span(u16, String),
+}
+
+#[derive(Debug, Clone)]
+pub enum DeltaMsg {
+    Input(Option<Input>),
+    Reference(Option<Input>),
+}
+
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum GraphMsg {
     RemoveCoord(Coord),
     AddCoord(Coord),
     ReplaceCoord { previous: Coord, new: Coord },
+    Interpolation(Interpolation),
 }
 
 impl From<SettingsMsg> for AppMsg {
@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use cosmic::iced_core::window;
 use data::{
     id::Id,
-    node::{Node, NodeType},
+    node::{Node, NodeType, ValueKind},
+    units::Units,
 };
 use derive_more::Unwrap;
 
@@ -25,11 +26,19 @@ pub enum NodeTypeC {
     Control(ControlC),
     Fan(FanC),
     Temp(TempC),
+    Metric(MetricC),
     CustomTemp(CustomTempC),
     Graph(GraphC),
     Flat(FlatC),
     Linear(LinearC),
     Target(TargetC),
+    Pid(PidC),
+    Filter(FilterC),
+    Delta(DeltaC),
+    Expression(ExpressionC),
+    Mix(MixC),
+    Switch(SwitchC),
+    Schedule(ScheduleC),
 }
 
 #[derive(Debug, Clone)]
@@ -41,6 +50,9 @@ pub struct FanC {}
 #[derive(Debug, Clone)]
 pub struct TempC {}
 
+#[derive(Debug, Clone)]
+pub struct MetricC {}
+
 #[derive(Debug, Clone)]
 pub struct CustomTempC {}
 
@@ -52,6 +64,9 @@ pub struct GraphC {
 #[derive(Debug, Clone)]
 pub struct FlatC {}
 
+#[derive(Debug, Clone)]
+pub struct DeltaC {}
+
 #[derive(Debug, Clone)]
 pub struct LinearC {
     pub min_temp: String,
@@ -68,17 +83,59 @@ pub struct TargetC {
     pub load_speed: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct PidC {
+    pub setpoint: String,
+    pub kp: String,
+    pub ki: String,
+    pub kd: String,
+    pub min_speed: String,
+    pub max_speed: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpressionC {}
+
+#[derive(Debug, Clone)]
+pub struct MixC {}
+
+#[derive(Debug, Clone)]
+pub struct SwitchC {
+    pub threshold: String,
+    pub hysteresis: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduleC {
+    pub override_threshold: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilterC {
+    pub span: String,
+}
+
 impl NodesC {
-    pub fn new<'a>(nodes: impl Iterator<Item = &'a Node>) -> Self {
+    pub fn new<'a>(nodes: impl Iterator<Item = &'a Node>, units: Units) -> Self {
         let mut data = HashMap::new();
 
         for node in nodes {
-            data.insert(node.id, NodeC::new(node));
+            data.insert(node.id, NodeC::new(node, units));
         }
 
         Self { data }
     }
 
+    /// The cached values are displayed in the units of the settings,
+    /// so they must be refreshed when the units change.
+    pub fn set_units<'a>(&mut self, nodes: impl Iterator<Item = &'a Node>, units: Units) {
+        for node in nodes {
+            if let Some(node_c) = self.data.get_mut(&node.id) {
+                node_c.node_type_c = NodeTypeC::new(&node.node_type, units);
+            }
+        }
+    }
+
     pub fn get(&self, id: &Id) -> &NodeC {
         self.data
             .get(id)
@@ -101,38 +158,63 @@ impl NodesC {
 }
 
 impl NodeC {
-    pub fn new(node: &Node) -> Self {
+    pub fn new(node: &Node, units: Units) -> Self {
         Self {
             name: node.name().clone(),
             context_menu_expanded: false,
-            node_type_c: NodeTypeC::new(&node.node_type),
+            node_type_c: NodeTypeC::new(&node.node_type, units),
             is_error_name: false,
         }
     }
 }
 
 impl NodeTypeC {
-    pub fn new(node_type: &NodeType) -> Self {
+    pub fn new(node_type: &NodeType, units: Units) -> Self {
+        let temp = |value: u8| units.display_text(value.into(), &ValueKind::Celsius);
+        let speed = |value: u8| units.display_text(value.into(), &ValueKind::Porcentage);
+
         match node_type {
             data::node::NodeType::Control(_) => NodeTypeC::Control(ControlC {}),
             data::node::NodeType::Fan(_) => NodeTypeC::Fan(FanC {}),
             data::node::NodeType::Temp(_) => NodeTypeC::Temp(TempC {}),
+            data::node::NodeType::Metric(_) => NodeTypeC::Metric(MetricC {}),
             data::node::NodeType::CustomTemp(_) => NodeTypeC::CustomTemp(CustomTempC {}),
             data::node::NodeType::Graph(_) => NodeTypeC::Graph(GraphC {
                 new_window_id: None,
             }),
             data::node::NodeType::Flat(_) => NodeTypeC::Flat(FlatC {}),
             data::node::NodeType::Linear(linear) => NodeTypeC::Linear(LinearC {
-                min_temp: linear.min_temp.to_string(),
-                min_speed: linear.min_speed.to_string(),
-                max_temp: linear.max_temp.to_string(),
-                max_speed: linear.max_speed.to_string(),
+                min_temp: temp(linear.min_temp),
+                min_speed: speed(linear.min_speed),
+                max_temp: temp(linear.max_temp),
+                max_speed: speed(linear.max_speed),
             }),
             data::node::NodeType::Target(target) => NodeTypeC::Target(TargetC {
-                idle_temp: target.idle_temp.to_string(),
-                idle_speed: target.idle_speed.to_string(),
-                load_temp: target.load_temp.to_string(),
-                load_speed: target.load_speed.to_string(),
+                idle_temp: temp(target.idle_temp),
+                idle_speed: speed(target.idle_speed),
+                load_temp: temp(target.load_temp),
+                load_speed: speed(target.load_speed),
+            }),
+            data::node::NodeType::Pid(pid) => NodeTypeC::Pid(PidC {
+                setpoint: temp(pid.setpoint),
+                kp: pid.kp.to_string(),
+                ki: pid.ki.to_string(),
+                kd: pid.kd.to_string(),
+                min_speed: speed(pid.min_speed),
+                max_speed: speed(pid.max_speed),
+            }),
+            data::node::NodeType::Filter(filter) => NodeTypeC::Filter(FilterC {
+                span: filter.span.to_string(),
+            }),
+            data::node::NodeType::Delta(_) => NodeTypeC::Delta(DeltaC {}),
+            data::node::NodeType::Expression(_) => NodeTypeC::Expression(ExpressionC {}),
+            data::node::NodeType::Mix(_) => NodeTypeC::Mix(MixC {}),
+            data::node::NodeType::Switch(switch) => NodeTypeC::Switch(SwitchC {
+                threshold: temp(switch.threshold),
+                hysteresis: units.display_text(switch.hysteresis.into(), &ValueKind::TempDelta),
+            }),
+            data::node::NodeType::Schedule(schedule) => NodeTypeC::Schedule(ScheduleC {
+                override_threshold: temp(schedule.override_threshold),
             }),
         }
     }
@@ -34,7 +34,7 @@ where
 pub mod input {
 
     use data::{
-        app_graph::Nodes,
+        app_graph::{depends_on, Nodes},
         id::Id,
         node::{Input, Node},
     };
@@ -63,6 +63,7 @@ pub mod input {
     }
 
     /// Return an iter of all inputs availlable for this node, minus his inputs
+    /// and the nodes which depend on it
     pub fn availlable_inputs<'a>(
         nodes: &'a Nodes,
         node: &'a Node,
@@ -79,6 +80,8 @@ pub mod input {
                         .map(|i| i.id)
                         .collect::<Vec<_>>()
                         .contains(&n.id)
+                    // would create a cycle
+                    && !depends_on(nodes, &n.id, &node.id)
             })
             .map(|n| Input {
                 id: n.id,
@@ -109,6 +112,33 @@ pub mod input {
                         .map(|i| i.id)
                         .collect::<Vec<_>>()
                         .contains(&n.id)
+                    // would create a cycle
+                    && !depends_on(nodes, &n.id, &node.id)
+            })
+            .map(|n| {
+                MyOption::Some(Input {
+                    id: n.id,
+                    name: n.name().clone(),
+                })
+            });
+
+        vec.extend(values);
+        vec
+    }
+
+    /// Like `optional_availlable_inputs`, but the inputs of the node are kept,
+    /// for nodes which can use the same input in several slots and restrict
+    /// the node types of each slot.
+    pub fn optional_slot_inputs(nodes: &Nodes, node: &Node, slot: usize) -> Vec<MyOption<Input>> {
+        let mut vec = vec![MyOption::None];
+
+        let values = nodes
+            .values()
+            .filter(|n| {
+                node.node_type
+                    .allowed_dep_at(slot)
+                    .contains(&n.node_type.to_light())
+                    && !depends_on(nodes, &n.id, &node.id)
             })
             .map(|n| {
                 MyOption::Some(Input {
@@ -4,7 +4,11 @@ use cosmic::{
     widget::{self, Row, Text},
     Element,
 };
-use data::{dir_manager::DirManager, settings::AppTheme};
+use data::{
+    dir_manager::DirManager,
+    settings::AppTheme,
+    units::{SpeedUnit, TempUnit},
+};
 
 use crate::{
     icon::icon_button,
@@ -27,6 +31,20 @@ pub fn settings_drawer(show: bool, dir_manager: &DirManager) -> Option<Element<'
         .add(
             widget::settings::item::builder(fl!("update_delay")).control(update_delay(dir_manager)),
         )
+        .add(
+            widget::settings::item::builder(fl!("temp_unit")).control(PickList::new(
+                TempUnit::VALUES.to_vec(),
+                Some(dir_manager.settings().temp_unit),
+                move |temp_unit| AppMsg::Settings(SettingsMsg::TempUnit(temp_unit)),
+            )),
+        )
+        .add(
+            widget::settings::item::builder(fl!("speed_unit")).control(PickList::new(
+                SpeedUnit::VALUES.to_vec(),
+                Some(dir_manager.settings().speed_unit),
+                move |speed_unit| AppMsg::Settings(SettingsMsg::SpeedUnit(speed_unit)),
+            )),
+        )
         .into()])
     .into();
 